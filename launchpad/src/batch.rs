@@ -0,0 +1,63 @@
+use crate::*;
+use std::collections::HashSet;
+
+//each launch attaches gas to `new` and its callback, so a batch has to fit in one call
+const MAX_BATCH_LAUNCHES: usize = 5;
+
+#[near_bindgen]
+impl Contract {
+    /// Launch several collections at once, paid from one attached deposit. The deposit has
+    /// to cover the sum of the launch costs and the excess is refunded right away. Every
+    /// collection is resolved by its own callback, which refunds its cost if it failed.
+    /// Returns the accounts the collections are deployed to.
+    #[payable]
+    pub fn launch_batch(&mut self, configs: Vec<LaunchConfig>) -> Vec<AccountId> {
+        require!(!configs.is_empty(), "Nothing to launch");
+        require!(
+            configs.len() <= MAX_BATCH_LAUNCHES,
+            format!("Can't launch more than {} collections at once", MAX_BATCH_LAUNCHES)
+        );
+
+        let creator_id = env::predecessor_account_id();
+        self.assert_can_launch(&creator_id);
+
+        let mut prefixes = HashSet::new();
+        let mut symbols = HashSet::new();
+        let launches: Vec<_> = configs
+            .into_iter()
+            .map(|config| {
+                let (collection, code, cost) = self.internal_prepare_launch(&creator_id, &config);
+                require!(
+                    prefixes.insert(collection.registry_key()),
+                    format!("{} appears more than once in the batch", collection.collection_id)
+                );
+                require!(
+                    symbols.insert(collection.symbol.to_lowercase()),
+                    format!("Symbol {} appears more than once in the batch", collection.symbol)
+                );
+                (config, collection, code, cost)
+            })
+            .collect();
+
+        // Charge the pooled cost once and hand back the rest
+        let total_cost: u128 = launches.iter().map(|(_, _, _, cost)| cost).sum();
+        let deposit = env::attached_deposit().as_yoctonear();
+        require!(
+            deposit >= total_cost,
+            format!("Attached deposit must be at least {} yoctoNEAR to launch these collections", total_cost)
+        );
+        if deposit > total_cost {
+            Promise::new(creator_id.clone()).transfer(NearToken::from_yoctonear(deposit - total_cost));
+        }
+
+        launches
+            .into_iter()
+            .map(|(config, collection, code, cost)| {
+                let collection_id = collection.collection_id.clone();
+                let init_args = config.template.init_args(&creator_id, &config, self.platform_fee.mint_fee_bps);
+                self.internal_deploy(collection, code, init_args, cost, cost, None);
+                collection_id
+            })
+            .collect()
+    }
+}
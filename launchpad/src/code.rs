@@ -0,0 +1,104 @@
+use crate::*;
+
+/// A version of the code of a collection template stored in the launchpad.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftCode {
+    pub template: Template,
+    pub version: String,
+    pub code_hash: Base58CryptoHash,
+    //length of the wasm in bytes
+    pub size: U64,
+    //block timestamp (in nanoseconds) of the upload
+    pub stored_at: U64,
+}
+
+impl Contract {
+    /// Resolve `version` (or the default version of `template` when `None`) to its code.
+    pub(crate) fn internal_nft_code(&self, template: Template, version: Option<String>) -> (String, Vec<u8>) {
+        let version = version
+            .or_else(|| self.default_nft_versions.get(&template))
+            .unwrap_or_else(|| {
+                env::panic_str(&format!("No code version is set as default for {:?}", template))
+            });
+        require!(
+            self.nft_codes.get(&version).map(|code| code.template) == Some(template),
+            format!("NFT code version {} is not stored for {:?}", version, template)
+        );
+        let code = self
+            .nft_code_blobs
+            .get(&version)
+            .unwrap_or_else(|| env::panic_str(&format!("NFT code version {} is not stored", version)));
+        (version, code)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Upload a new wasm for `template` under `version`. Only admins can store code, and the
+    /// attached deposit has to cover the storage it takes; the excess is refunded.
+    ///
+    /// Arguments are borsh-serialized so the wasm doesn't have to be encoded as JSON.
+    #[payable]
+    pub fn store_nft_code(
+        &mut self,
+        #[serializer(borsh)] template: Template,
+        #[serializer(borsh)] version: String,
+        #[serializer(borsh)] code: Vec<u8>,
+    ) -> NftCode {
+        self.assert_role(Role::Admin);
+        require!(!code.is_empty(), "NFT code can't be empty");
+        require!(
+            self.nft_codes.get(&version).is_none(),
+            format!("NFT code version {} is already stored", version)
+        );
+
+        let initial_storage = env::storage_usage();
+
+        let nft_code = NftCode {
+            template,
+            version: version.clone(),
+            code_hash: Base58CryptoHash::from(env::sha256_array(&code)),
+            size: U64(code.len() as u64),
+            stored_at: U64(env::block_timestamp()),
+        };
+        self.nft_code_blobs.insert(&version, &code);
+        self.nft_codes.insert(&version, &nft_code);
+
+        refund_storage_deposit(initial_storage);
+
+        Event::StoreNftCode {
+            template,
+            version: &nft_code.version,
+            code_hash: &nft_code.code_hash,
+        }
+        .emit();
+
+        nft_code
+    }
+
+    /// Set the version of `template` used by `launch` when no version is given. Only admins
+    /// can call it.
+    pub fn set_default_version(&mut self, template: Template, version: String) {
+        self.assert_role(Role::Admin);
+        require!(
+            self.nft_codes.get(&version).map(|code| code.template) == Some(template),
+            format!("NFT code version {} is not stored for {:?}", version, template)
+        );
+        self.default_nft_versions.insert(&template, &version);
+    }
+
+    pub fn get_default_version(&self, template: Template) -> Option<String> {
+        self.default_nft_versions.get(&template)
+    }
+
+    pub fn get_nft_code(&self, version: String) -> Option<NftCode> {
+        self.nft_codes.get(&version)
+    }
+
+    /// Every stored NFT code version, in upload order.
+    pub fn get_nft_codes(&self) -> Vec<NftCode> {
+        self.nft_codes.values().collect()
+    }
+}
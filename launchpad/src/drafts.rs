@@ -0,0 +1,135 @@
+use crate::*;
+
+/// A launch configuration saved by a creator to be deployed later.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct LaunchDraft {
+    pub draft_id: U64,
+    pub creator_id: AccountId,
+    pub config: LaunchConfig,
+    //block timestamp (in nanoseconds) of the last save
+    pub updated_at: U64,
+}
+
+impl Contract {
+    fn internal_creator_draft(&self, draft_id: U64) -> LaunchDraft {
+        let draft = self
+            .drafts
+            .get(&draft_id.0)
+            .unwrap_or_else(|| env::panic_str("Draft not found"));
+        require!(
+            draft.creator_id == env::predecessor_account_id(),
+            "Only the creator of the draft can use it"
+        );
+        draft
+    }
+
+    fn internal_remove_draft(&mut self, draft: &LaunchDraft) {
+        self.drafts.remove(&draft.draft_id.0);
+        if let Some(mut by_creator) = self.drafts_by_creator.get(&draft.creator_id) {
+            by_creator.remove(&draft.draft_id.0);
+            if by_creator.is_empty() {
+                self.drafts_by_creator.remove(&draft.creator_id);
+            } else {
+                self.drafts_by_creator.insert(&draft.creator_id, &by_creator);
+            }
+        }
+    }
+
+    /// Remove a draft once it was launched and refund the storage it frees to its creator.
+    pub(crate) fn internal_finish_draft(&mut self, draft_id: U64) {
+        // The creator may have deleted the draft while the launch was in flight
+        if let Some(draft) = self.drafts.get(&draft_id.0) {
+            let initial_storage = env::storage_usage();
+            self.internal_remove_draft(&draft);
+            let freed = NEAR_PER_STORAGE * (initial_storage - env::storage_usage()) as u128;
+            if freed > 0 {
+                Promise::new(draft.creator_id).transfer(NearToken::from_yoctonear(freed));
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Save `config` as a draft of the caller. The attached deposit has to cover the storage
+    /// of the draft; the excess is refunded.
+    #[payable]
+    pub fn save_draft(&mut self, config: LaunchConfig) -> U64 {
+        let creator_id = env::predecessor_account_id();
+        self.assert_valid_launch(&config);
+        let initial_storage = env::storage_usage();
+
+        let draft_id = self.next_draft_id;
+        self.next_draft_id += 1;
+        self.drafts.insert(
+            &draft_id,
+            &LaunchDraft {
+                draft_id: U64(draft_id),
+                creator_id: creator_id.clone(),
+                config,
+                updated_at: U64(env::block_timestamp()),
+            },
+        );
+
+        let mut by_creator = self.drafts_by_creator.get(&creator_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::DraftsByCreatorInner {
+                account_hash: env::sha256_array(creator_id.as_bytes()),
+            })
+        });
+        by_creator.insert(&draft_id);
+        self.drafts_by_creator.insert(&creator_id, &by_creator);
+
+        refund_storage_deposit(initial_storage);
+        U64(draft_id)
+    }
+
+    /// Replace the configuration of a draft. Extra storage is paid from the attached deposit
+    /// and freed storage is refunded.
+    #[payable]
+    pub fn update_draft(&mut self, draft_id: U64, config: LaunchConfig) {
+        let mut draft = self.internal_creator_draft(draft_id);
+        self.assert_valid_launch(&config);
+        let initial_storage = env::storage_usage();
+
+        draft.config = config;
+        draft.updated_at = U64(env::block_timestamp());
+        self.drafts.insert(&draft_id.0, &draft);
+
+        refund_storage_deposit(initial_storage);
+    }
+
+    /// Delete a draft and refund its storage.
+    pub fn delete_draft(&mut self, draft_id: U64) {
+        let draft = self.internal_creator_draft(draft_id);
+        let initial_storage = env::storage_usage();
+        self.internal_remove_draft(&draft);
+        refund_storage_deposit(initial_storage);
+    }
+
+    /// Launch a saved draft. It costs the same as `launch` with its configuration. The draft
+    /// is only removed once the collection is deployed, and the storage it frees is refunded
+    /// then; a failed launch keeps it.
+    #[payable]
+    pub fn launch_draft(&mut self, draft_id: U64) -> Promise {
+        let draft = self.internal_creator_draft(draft_id);
+        self.internal_launch(
+            draft.creator_id,
+            draft.config,
+            env::attached_deposit().as_yoctonear(),
+            Some(draft_id),
+        )
+    }
+
+    pub fn get_draft(&self, draft_id: U64) -> Option<LaunchDraft> {
+        self.drafts.get(&draft_id.0)
+    }
+
+    pub fn get_drafts_by_creator(&self, creator_id: AccountId) -> Vec<LaunchDraft> {
+        self.drafts_by_creator
+            .get(&creator_id)
+            .map(|ids| ids.iter().filter_map(|id| self.drafts.get(&id)).collect())
+            .unwrap_or_default()
+    }
+}
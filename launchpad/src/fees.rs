@@ -0,0 +1,135 @@
+use crate::*;
+
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas::from_tgas(10);
+
+/// Fees the launchpad charges: a flat fee on every launch and a cut of every mint.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlatformFee {
+    //yoctoNEAR charged on top of the launch cost
+    pub launch_fee: U128,
+    //basis points of each mint price sent to the launchpad by the collection
+    pub mint_fee_bps: U128,
+}
+
+trait FungibleTokenReceiver {
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128;
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Collections pay the mint fee of FT-priced mints with `ft_transfer_call`.
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+        let ft_contract_id = env::predecessor_account_id();
+        require!(msg == "platform_fee", "Unsupported transfer");
+        log!("Platform fee of {} {} from {}", amount.0, ft_contract_id, sender_id);
+
+        let accrued = self.accrued_ft_fees.get(&ft_contract_id).unwrap_or(0);
+        self.accrued_ft_fees.insert(&ft_contract_id, &(accrued + amount.0));
+
+        U128(0)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Collections pay the mint fee of NEAR-priced mints by attaching it to this call.
+    #[payable]
+    pub fn deposit_platform_fee(&mut self) {
+        self.accrued_fees += env::attached_deposit().as_yoctonear();
+    }
+
+    pub fn set_platform_fee(&mut self, launch_fee: U128, mint_fee_bps: U128) {
+        self.assert_owner();
+        require!(mint_fee_bps.0 <= 10_000, "mint_fee_bps can't be more than 10000");
+        self.platform_fee = PlatformFee {
+            launch_fee,
+            mint_fee_bps,
+        };
+    }
+
+    pub fn set_treasury(&mut self, treasury_id: AccountId) {
+        self.assert_owner();
+        self.treasury_id = treasury_id;
+    }
+
+    /// Pay out the accrued NEAR fees, or the fees accrued in `token_id` when given, to the
+    /// treasury. Only the owner can call it.
+    pub fn withdraw_platform_fees(&mut self, token_id: Option<AccountId>) -> Promise {
+        self.assert_owner();
+
+        let (amount, transfer) = if let Some(token_id) = token_id.clone() {
+            let amount = self.accrued_ft_fees.get(&token_id).unwrap_or(0);
+            require!(amount > 0, "No fees to withdraw");
+            self.accrued_ft_fees.insert(&token_id, &0);
+
+            let transfer = Promise::new(token_id).function_call(
+                "ft_transfer".to_string(),
+                json!({
+                    "receiver_id": self.treasury_id.to_string(),
+                    "amount": amount.to_string(),
+                })
+                .to_string()
+                .into_bytes()
+                .to_vec(),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_FT_TRANSFER,
+            );
+            (amount, transfer)
+        } else {
+            let amount = self.accrued_fees;
+            require!(amount > 0, "No fees to withdraw");
+            self.accrued_fees = 0;
+
+            let transfer = Promise::new(self.treasury_id.clone()).transfer(NearToken::from_yoctonear(amount));
+            (amount, transfer)
+        };
+
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+                .resolve_withdraw_platform_fees(token_id, U128(amount)),
+        )
+    }
+
+    /// Callback of `withdraw_platform_fees`. Puts the fees back if the payout failed.
+    #[private]
+    pub fn resolve_withdraw_platform_fees(&mut self, token_id: Option<AccountId>, amount: U128) -> bool {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        if succeeded {
+            Event::WithdrawPlatformFees {
+                treasury_id: &self.treasury_id,
+                token_id: token_id.as_ref(),
+                amount: &amount,
+            }
+            .emit();
+        } else if let Some(token_id) = token_id {
+            let accrued = self.accrued_ft_fees.get(&token_id).unwrap_or(0);
+            self.accrued_ft_fees.insert(&token_id, &(accrued + amount.0));
+        } else {
+            self.accrued_fees += amount.0;
+        }
+
+        succeeded
+    }
+
+    pub fn get_platform_fee(&self) -> PlatformFee {
+        self.platform_fee.clone()
+    }
+
+    pub fn get_treasury(&self) -> AccountId {
+        self.treasury_id.clone()
+    }
+
+    /// Fees accrued and not yet withdrawn, in NEAR or in `token_id` when given.
+    pub fn get_accrued_fees(&self, token_id: Option<AccountId>) -> U128 {
+        U128(if let Some(token_id) = token_id {
+            self.accrued_ft_fees.get(&token_id).unwrap_or(0)
+        } else {
+            self.accrued_fees
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use crate::*;
+
+impl Contract {
+    /// Validate an FT launch and work out the registry entry, the code and the total cost of
+    /// launching it for `creator_id`.
+    fn internal_prepare_launch_ft(
+        &self,
+        creator_id: &AccountId,
+        metadata: &FungibleTokenMetadata,
+        total_supply: U128,
+        version: Option<String>,
+        prefix: Option<String>,
+    ) -> (Collection, Vec<u8>, u128) {
+        metadata.assert_valid();
+        require!(total_supply.0 > 0, "total_supply has to be greater than 0");
+        self.assert_symbol_available(&metadata.symbol);
+        let prefix = collection_prefix(&metadata.symbol, prefix.as_ref());
+        self.assert_prefix_available(&prefix);
+
+        let (code_version, code) = self.internal_nft_code(Template::FungibleToken, version);
+        let collection = Collection {
+            creator_id: creator_id.clone(),
+            collection_id: collection_account_id(&prefix),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            base_uri: None,
+            total_supply,
+            mint_price: U128(0),
+            mint_currency: None,
+            payment_split_percent: U128(0),
+            burn_fee: U128(0),
+            sale_start: None,
+            sale_end: None,
+            template: Template::FungibleToken,
+            code_version,
+            launched_at: U64(env::block_timestamp()),
+            last_upgrade: None,
+            status: CollectionStatus::Active,
+            redeem_deadline: None,
+            swept_amount: None,
+            delisted: false,
+        };
+
+        let cost = internal_account_cost(Template::FungibleToken, code.len())
+            + internal_collection_storage_cost(&collection)
+            + self.platform_fee.launch_fee.0;
+        (collection, code, cost)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Launch a NEP-141 fungible token as a sub-account of the launchpad, minting
+    /// `total_supply` to the caller. It deploys the stored fungible token code `version` (or
+    /// its default version when `None`) and is charged like `launch`. As with `launch`,
+    /// `prefix` overrides the sub-account prefix.
+    #[payable]
+    pub fn launch_ft(
+        &mut self,
+        metadata: FungibleTokenMetadata,
+        total_supply: U128,
+        version: Option<String>,
+        prefix: Option<String>,
+    ) -> Promise {
+        let creator_id = env::predecessor_account_id();
+        self.assert_can_launch(&creator_id);
+        let (collection, code, cost) =
+            self.internal_prepare_launch_ft(&creator_id, &metadata, total_supply, version, prefix);
+
+        let init_args = json!({
+            "owner_id": creator_id.to_string(),
+            "total_supply": total_supply,
+            "metadata": metadata,
+        });
+        self.internal_deploy(collection, code, init_args, cost, env::attached_deposit().as_yoctonear(), None)
+    }
+
+    /// Exact amount of yoctoNEAR that `launch_ft` keeps for the given token.
+    pub fn quote_launch_ft(
+        &self,
+        creator_id: AccountId,
+        metadata: FungibleTokenMetadata,
+        total_supply: U128,
+        version: Option<String>,
+        prefix: Option<String>,
+    ) -> U128 {
+        let (_, _, cost) =
+            self.internal_prepare_launch_ft(&creator_id, &metadata, total_supply, version, prefix);
+        U128(cost)
+    }
+}
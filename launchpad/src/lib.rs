@@ -1,98 +1,409 @@
 // Find all our documentation at https://docs.near.org
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
 use near_sdk::{
-    near_bindgen, AccountId, env, Promise, NearToken, Gas,
-    serde_json::json, log
+    near_bindgen, AccountId, env, require, Promise, PromiseResult, NearToken, Gas, BorshStorageKey,
+    CryptoHash, PanicOnDefault, serde_json::json, log
 };
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Base58CryptoHash, U128, U64};
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
 use near_contract_standards::non_fungible_token::metadata::{
     NFTContractMetadata
 };
-use near_sdk::serde::Serialize;
+use near_sdk::serde::{Deserialize, Serialize};
+
+pub use crate::code::NftCode;
+pub use crate::drafts::LaunchDraft;
+pub use crate::fees::PlatformFee;
+pub use crate::registry::{Collection, CollectionStatus};
+pub use crate::roles::{LaunchMode, Role};
+pub use crate::templates::Template;
+pub use crate::upgrade::CollectionUpgrade;
+use crate::registry::internal_collection_storage_cost;
+use crate::validation::{collection_account_id, collection_prefix};
+
+mod batch;
+mod code;
+mod drafts;
+mod fees;
+mod ft_launch;
+mod registry;
+mod roles;
+mod templates;
+mod upgrade;
+mod validation;
 
 const NEAR_PER_STORAGE: u128 = 10_000_000_000_000_000_000;
 const NFT_CONTRACT_STORAGE: u128 = 30_000_000_000_000_000_000_000;
+const FT_CONTRACT_STORAGE: u128 = 10_000_000_000_000_000_000_000;
+const GAS_FOR_RESOLVE_LAUNCH: Gas = Gas::from_tgas(10);
+//key near-sdk stores the contract struct under
+const STATE_KEY: &[u8] = b"STATE";
+
+/// Everything a creator chooses when launching a collection. Drafts store it as-is.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct LaunchConfig {
+    #[serde(default)]
+    pub template: Template,
+    pub metadata: NFTContractMetadata,
+    pub total_supply: U128,
+    pub mint_price: U128,
+    pub mint_currency: Option<AccountId>,
+    pub payment_split_percent: U128,
+    pub burn_fee: U128,
+    //stored code version of the template to deploy, its default version when `None`
+    pub version: Option<String>,
+    //nanosecond timestamps bounding when minting is open
+    pub sale_start: Option<U64>,
+    pub sale_end: Option<U64>,
+    //sub-account prefix to deploy to instead of the lowercase symbol
+    pub prefix: Option<String>,
+    //hash of the final artwork committed to before the reveal
+    pub provenance_hash: Option<String>,
+    //metadata reference tokens show until the reveal
+    pub placeholder_uri: Option<String>,
+}
 
 // Define the contract structure
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
-pub struct Contract {}
+pub struct Contract {
+    pub owner_id: AccountId,
 
-impl Default for Contract {
-    fn default() -> Self {
-        Self {}
-    }
+    //account that has to call `accept_ownership` to become the owner
+    pub proposed_owner_id: Option<AccountId>,
+
+    //accounts holding each role
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+
+    pub launch_mode: LaunchMode,
+
+    //every collection launched through this contract, keyed by its sub-account prefix
+    pub collections: UnorderedMap<String, Collection>,
+
+    //prefixes of the collections launched by each creator
+    pub collections_by_creator: LookupMap<AccountId, UnorderedSet<String>>,
+
+    //prefix of the collection launched under each lowercase symbol
+    pub collection_prefixes: LookupMap<String, String>,
+
+    //metadata of every code version that can be launched, keyed by version
+    pub nft_codes: UnorderedMap<String, NftCode>,
+
+    //the wasm of every stored code version
+    pub nft_code_blobs: LookupMap<String, Vec<u8>>,
+
+    //version of each template used when `launch` isn't given one
+    pub default_nft_versions: LookupMap<Template, String>,
+
+    pub platform_fee: PlatformFee,
+
+    //account the platform fees are paid out to
+    pub treasury_id: AccountId,
+
+    //NEAR fees accrued and not yet withdrawn
+    pub accrued_fees: u128,
+
+    //fees accrued per fungible token from FT-priced mints
+    pub accrued_ft_fees: LookupMap<AccountId, u128>,
+
+    //launch configurations saved by creators, keyed by draft ID
+    pub drafts: UnorderedMap<u64, LaunchDraft>,
+
+    //IDs of the drafts saved by each creator
+    pub drafts_by_creator: LookupMap<AccountId, UnorderedSet<u64>>,
+
+    pub next_draft_id: u64,
+
+    //prefixes of the collections being deployed, whose symbols are already in
+    //`collection_prefixes`
+    pub pending_prefixes: LookupSet<String>,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
+enum StorageKey {
+    RoleMembers,
+    RoleMembersInner { role: Role },
+    Collections,
+    CollectionsByCreator,
+    CollectionsByCreatorInner { account_hash: CryptoHash },
+    NftCodes,
+    NftCodeBlobs,
+    DefaultNftVersions,
+    AccruedFtFees,
+    Drafts,
+    DraftsByCreator,
+    DraftsByCreatorInner { account_hash: CryptoHash },
+    CollectionPrefixes,
+    PendingPrefixes,
 }
 
 // Implement the contract structure
 #[near_bindgen]
 impl Contract {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        Self {
+            owner_id: owner_id.clone(),
+            proposed_owner_id: None,
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+            launch_mode: LaunchMode::Permissionless,
+            collections: UnorderedMap::new(StorageKey::Collections),
+            collections_by_creator: LookupMap::new(StorageKey::CollectionsByCreator),
+            collection_prefixes: LookupMap::new(StorageKey::CollectionPrefixes),
+            nft_codes: UnorderedMap::new(StorageKey::NftCodes),
+            nft_code_blobs: LookupMap::new(StorageKey::NftCodeBlobs),
+            default_nft_versions: LookupMap::new(StorageKey::DefaultNftVersions),
+            platform_fee: PlatformFee {
+                launch_fee: U128(0),
+                mint_fee_bps: U128(0),
+            },
+            treasury_id: owner_id,
+            accrued_fees: 0,
+            accrued_ft_fees: LookupMap::new(StorageKey::AccruedFtFees),
+            drafts: UnorderedMap::new(StorageKey::Drafts),
+            drafts_by_creator: LookupMap::new(StorageKey::DraftsByCreator),
+            next_draft_id: 0,
+            pending_prefixes: LookupSet::new(StorageKey::PendingPrefixes),
+        }
+    }
+
+    /// Run after every deployment of new code. The first release of the launchpad stored an
+    /// empty struct; that state is replaced by a fresh one owned by the launchpad account,
+    /// which can then hand ownership over with `propose_owner`. Any other state is kept.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        match env::storage_read(STATE_KEY) {
+            Some(state) if state.is_empty() => Self::new(env::current_account_id()),
+            Some(_) => env::state_read().unwrap_or_else(|| env::panic_str("Contract state is missing")),
+            None => env::panic_str("Contract is not initialized, call new instead"),
+        }
+    }
+
+    /// Launch a new NFT collection as a sub-account of the launchpad from `config.template`
+    /// (classic when left out), deploying its stored code `config.version` (or its default
+    /// version when `None`). Minting is only open between `sale_start` and `sale_end`
+    /// (nanosecond timestamps) when they are given. The collection is deployed to
+    /// `<prefix>.<launchpad>`, where the prefix is the lowercase symbol unless `prefix`
+    /// overrides it.
     #[payable]
-    pub fn launch(
+    pub fn launch(&mut self, config: LaunchConfig) -> Promise {
+        self.internal_launch(
+            env::predecessor_account_id(),
+            config,
+            env::attached_deposit().as_yoctonear(),
+            None,
+        )
+    }
+
+    /// Exact amount of yoctoNEAR that `launch` keeps for the given configuration: the NFT
+    /// contract code and state, the registry entry and the platform launch fee. Anything
+    /// attached above it is refunded.
+    pub fn quote_launch(&self, creator_id: AccountId, config: LaunchConfig) -> U128 {
+        let (_, _, cost) = self.internal_prepare_launch(&creator_id, &config);
+        U128(cost)
+    }
+
+    /// Callback of `launch` and `launch_ft`. Records the collection, accrues `launch_fee`,
+    /// removes the launched draft `draft_id` and emits `launch` (or `launch_ft`) if the
+    /// deployment succeeded, otherwise emits `launch_failed`, releases the symbol and prefix
+    /// reserved for the collection and refunds `deposit` to the creator.
+    #[private]
+    pub fn resolve_launch(
+        &mut self,
+        collection: Collection,
+        deposit: U128,
+        launch_fee: U128,
+        draft_id: Option<U64>,
+    ) -> bool {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        self.internal_release_launch(&collection, succeeded);
+
+        if succeeded {
+            if collection.template == Template::FungibleToken {
+                Event::LaunchFt {
+                    creator_id: &collection.creator_id,
+                    token_id: &collection.collection_id,
+                    total_supply: &collection.total_supply,
+                    name: &collection.name,
+                    symbol: &collection.symbol,
+                }
+                .emit();
+            } else {
+                Event::Launch {
+                    creator_id: &collection.creator_id,
+                    collection_id: &collection.collection_id,
+                    total_supply: &collection.total_supply,
+                    mint_price: &collection.mint_price,
+                    payment_split_percent: &collection.payment_split_percent,
+                    mint_currency: collection.mint_currency.as_ref(),
+                    name: &collection.name,
+                    symbol: &collection.symbol,
+                    base_uri: &collection.base_uri,
+                    template: collection.template,
+                }
+                .emit();
+            }
+
+            self.internal_add_collection(collection);
+            self.accrued_fees += launch_fee.0;
+            if let Some(draft_id) = draft_id {
+                self.internal_finish_draft(draft_id);
+            }
+        } else {
+            Event::LaunchFailed {
+                creator_id: &collection.creator_id,
+                collection_id: &collection.collection_id,
+                symbol: &collection.symbol,
+                refund: &deposit,
+            }
+            .emit();
+
+            if deposit.0 > 0 {
+                Promise::new(collection.creator_id.clone()).transfer(NearToken::from_yoctonear(deposit.0));
+            }
+        }
+
+        succeeded
+    }
+}
+
+impl Contract {
+    /// Validate `config` and work out the registry entry, the code and the total cost of
+    /// launching it for `creator_id`.
+    pub(crate) fn internal_prepare_launch(
+        &self,
+        creator_id: &AccountId,
+        config: &LaunchConfig,
+    ) -> (Collection, Vec<u8>, u128) {
+        self.assert_valid_launch(config);
+
+        let (code_version, code) = self.internal_nft_code(config.template, config.version.clone());
+        let metadata = &config.metadata;
+        let collection = Collection {
+            creator_id: creator_id.clone(),
+            collection_id: collection_account_id(&collection_prefix(&metadata.symbol, config.prefix.as_ref())),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            base_uri: metadata.base_uri.clone(),
+            total_supply: config.total_supply,
+            mint_price: config.mint_price,
+            mint_currency: config.mint_currency.clone(),
+            payment_split_percent: config.payment_split_percent,
+            burn_fee: config.burn_fee,
+            sale_start: config.sale_start,
+            sale_end: config.sale_end,
+            template: config.template,
+            code_version,
+            launched_at: U64(env::block_timestamp()),
+            last_upgrade: None,
+            status: CollectionStatus::Active,
+            redeem_deadline: None,
+            swept_amount: None,
+            delisted: false,
+        };
+
+        let cost = internal_account_cost(config.template, code.len())
+            + internal_collection_storage_cost(&collection)
+            + self.platform_fee.launch_fee.0;
+        (collection, code, cost)
+    }
+
+    /// Deploy the collection described by `config` for `creator_id`, who attached `deposit`.
+    /// `draft_id` is the draft the configuration comes from, if any.
+    pub(crate) fn internal_launch(
         &mut self,
-        metadata: NFTContractMetadata,
-        total_supply: U128,
-        mint_price: U128,
-        mint_currency: Option<AccountId>,
-        payment_split_percent: U128,
-        burn_fee: U128,
-    ) {
-        let current_id = env::current_account_id();
-        let owner = env::predecessor_account_id(); 
-
-        let code = include_bytes!("./nft/nft.wasm").to_vec();
-        let contract_bytes = code.len() as u128;
-        let minimum_needed = NEAR_PER_STORAGE * contract_bytes + NFT_CONTRACT_STORAGE;
-
-        // Deploy the nft contract
-        let nft_contract_id: AccountId = format!("{}.{}", metadata.symbol.to_lowercase(), current_id).parse().unwrap();
-
-        Promise::new(nft_contract_id.clone())
+        creator_id: AccountId,
+        config: LaunchConfig,
+        deposit: u128,
+        draft_id: Option<U64>,
+    ) -> Promise {
+        self.assert_can_launch(&creator_id);
+        let (collection, code, cost) = self.internal_prepare_launch(&creator_id, &config);
+        let init_args = config.template.init_args(&creator_id, &config, self.platform_fee.mint_fee_bps);
+
+        self.internal_deploy(collection, code, init_args, cost, deposit, draft_id)
+    }
+
+    /// Create the account of `collection`, deploy `code` and initialize it with `init_args`.
+    /// The exact launch `cost` is kept out of `deposit` and the rest is refunded right away.
+    pub(crate) fn internal_deploy(
+        &mut self,
+        collection: Collection,
+        code: Vec<u8>,
+        init_args: near_sdk::serde_json::Value,
+        cost: u128,
+        deposit: u128,
+        draft_id: Option<U64>,
+    ) -> Promise {
+        // Charge exactly what the launch costs and hand back the rest
+        require!(
+            deposit >= cost,
+            format!("Attached deposit must be at least {} yoctoNEAR to launch this collection", cost)
+        );
+        if deposit > cost {
+            Promise::new(collection.creator_id.clone()).transfer(NearToken::from_yoctonear(deposit - cost));
+        }
+        // Hold the symbol and the prefix until `resolve_launch`, so a concurrent launch can't
+        // take them
+        self.internal_reserve_launch(&collection);
+
+        Promise::new(collection.collection_id.clone())
             .create_account()
-            .transfer(NearToken::from_yoctonear(minimum_needed))
+            .transfer(NearToken::from_yoctonear(internal_account_cost(collection.template, code.len())))
             .deploy_contract(code)
             .function_call(
                 "new".to_string(),
-                if let Some(mint_currency) = mint_currency.clone() {
-                    json!({
-                        "owner_id": owner.to_string(),
-                        "metadata": metadata,
-                        "total_supply": total_supply.0.to_string(),
-                        "mint_price": mint_price.0.to_string(),
-                        "mint_currency": mint_currency.to_string(),
-                        "payment_split_percent": payment_split_percent.0.to_string(),
-                        "burn_fee": burn_fee.0.to_string(),
-                    })
-                } else {
-                    json!({
-                        "owner_id": owner.to_string(),
-                        "metadata": metadata,
-                        "total_supply": total_supply.0.to_string(),
-                        "mint_price": mint_price.0.to_string(),
-                        "payment_split_percent": payment_split_percent.0.to_string(),
-                        "burn_fee": burn_fee.0.to_string(),
-                    })
-                }.to_string().into_bytes().to_vec(),
+                init_args.to_string().into_bytes().to_vec(),
                 NearToken::from_yoctonear(0),
                 Gas::from_tgas(20)
-            );
-        
-            Event::Launch {
-                creator_id: &owner,
-                collection_id: &nft_contract_id,
-                total_supply: &total_supply,
-                mint_price: &mint_price,
-                payment_split_percent: &payment_split_percent,
-                mint_currency: mint_currency.as_ref(),
-                name: &metadata.name,
-                symbol: &metadata.symbol,
-                base_uri: &metadata.base_uri,
-            }
-            .emit();
+            )
+            .then(
+                // Only record the collection once the account exists and `new` succeeded
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_LAUNCH)
+                    .resolve_launch(collection, U128(cost), self.platform_fee.launch_fee, draft_id)
+            )
+    }
+}
+
+/// Charge the caller for the storage used since `initial_storage` out of the attached deposit
+/// and refund the rest. If storage was freed instead, its cost is refunded with the deposit.
+pub(crate) fn refund_storage_deposit(initial_storage: u64) {
+    let deposit = env::attached_deposit().as_yoctonear();
+    let current_storage = env::storage_usage();
+
+    let refund = if current_storage >= initial_storage {
+        let storage_cost = NEAR_PER_STORAGE * (current_storage - initial_storage) as u128;
+        require!(
+            deposit >= storage_cost,
+            format!("Attached deposit must be at least {} yoctoNEAR to cover storage", storage_cost)
+        );
+        deposit - storage_cost
+    } else {
+        deposit + NEAR_PER_STORAGE * (initial_storage - current_storage) as u128
+    };
+
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
     }
 }
 
+/// Amount transferred to a new account of `template` to cover its code and initial state.
+pub(crate) fn internal_account_cost(template: Template, code_len: usize) -> u128 {
+    let state_storage = if template == Template::FungibleToken {
+        FT_CONTRACT_STORAGE
+    } else {
+        NFT_CONTRACT_STORAGE
+    };
+    NEAR_PER_STORAGE * code_len as u128 + state_storage
+}
+
 #[derive(Serialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 #[serde(tag = "event", content = "data")]
@@ -110,7 +421,65 @@ pub enum Event<'a> {
         base_uri: &'a Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         mint_currency: Option<&'a AccountId>,
-    }
+        template: Template,
+    },
+    LaunchFt {
+        creator_id: &'a AccountId,
+        token_id: &'a AccountId,
+        total_supply: &'a U128,
+        name: &'a String,
+        symbol: &'a String,
+    },
+    LaunchFailed {
+        creator_id: &'a AccountId,
+        collection_id: &'a AccountId,
+        symbol: &'a String,
+        refund: &'a U128,
+    },
+    StoreNftCode {
+        template: Template,
+        version: &'a String,
+        code_hash: &'a Base58CryptoHash,
+    },
+    UpgradeCollection {
+        collection_id: &'a AccountId,
+        from_version: &'a String,
+        to_version: &'a String,
+        succeeded: bool,
+    },
+    WithdrawPlatformFees {
+        treasury_id: &'a AccountId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token_id: Option<&'a AccountId>,
+        amount: &'a U128,
+    },
+    GrantRole {
+        role: Role,
+        account_id: &'a AccountId,
+    },
+    RevokeRole {
+        role: Role,
+        account_id: &'a AccountId,
+    },
+    TransferOwnership {
+        old_owner_id: &'a AccountId,
+        new_owner_id: &'a AccountId,
+    },
+    SetCollectionStatus {
+        collection_id: &'a AccountId,
+        status: CollectionStatus,
+        moderator_id: &'a AccountId,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'a String>,
+    },
+    CloseCollection {
+        collection_id: &'a AccountId,
+        redeem_deadline: U64,
+    },
+    SweepCollection {
+        collection_id: &'a AccountId,
+        amount: &'a U128,
+    },
 }
 
 impl Event<'_> {
@@ -138,4 +507,204 @@ pub(crate) fn emit_event<T: ?Sized + Serialize>(data: &T) {
     })
     .to_string();
     log!(format!("EVENT_JSON:{}", event_json));
-}
\ No newline at end of file
+}
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use near_contract_standards::non_fungible_token::metadata::NFT_METADATA_SPEC;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const NFT_CODE: &[u8] = b"nft code";
+
+    pub(crate) fn context(predecessor: AccountId, deposit: u128) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id("launchpad.near".parse().unwrap())
+            .predecessor_account_id(predecessor)
+            .attached_deposit(NearToken::from_yoctonear(deposit));
+        builder
+    }
+
+    /// A launchpad owned by `accounts(0)` with a default classic code version.
+    pub(crate) fn setup() -> Contract {
+        testing_env!(context(accounts(0), 10u128.pow(24)).build());
+        let mut contract = Contract::new(accounts(0));
+        contract.store_nft_code(Template::Classic, "1.0.0".to_string(), NFT_CODE.to_vec());
+        contract.set_default_version(Template::Classic, "1.0.0".to_string());
+        contract
+    }
+
+    pub(crate) fn launch_config(symbol: &str) -> LaunchConfig {
+        LaunchConfig {
+            template: Template::Classic,
+            metadata: NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: format!("{} collection", symbol),
+                symbol: symbol.to_string(),
+                icon: None,
+                base_uri: None,
+                reference: None,
+                reference_hash: None,
+            },
+            total_supply: U128(100),
+            mint_price: U128(10u128.pow(24)),
+            mint_currency: None,
+            payment_split_percent: U128(50),
+            burn_fee: U128(10),
+            version: None,
+            sale_start: None,
+            sale_end: None,
+            prefix: None,
+            provenance_hash: None,
+            placeholder_uri: None,
+        }
+    }
+
+    /// Transfers the contract created, as (receiver, yoctoNEAR).
+    fn transfers() -> Vec<(AccountId, u128)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    MockAction::Transfer { deposit, .. } => Some((receiver_id.clone(), deposit.as_yoctonear())),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quote_launch_covers_code_state_registry_and_fee() {
+        let mut contract = setup();
+        let quote = contract.quote_launch(accounts(1), launch_config("ART")).0;
+        let (collection, _, _) = contract.internal_prepare_launch(&accounts(1), &launch_config("ART"));
+        assert_eq!(
+            quote,
+            NEAR_PER_STORAGE * NFT_CODE.len() as u128
+                + NFT_CONTRACT_STORAGE
+                + internal_collection_storage_cost(&collection)
+        );
+
+        contract.set_platform_fee(U128(500), U128(0));
+        assert_eq!(contract.quote_launch(accounts(1), launch_config("ART")).0, quote + 500);
+    }
+
+    #[test]
+    fn launch_refunds_the_excess_deposit() {
+        let mut contract = setup();
+        let quote = contract.quote_launch(accounts(1), launch_config("ART")).0;
+
+        testing_env!(context(accounts(1), quote + 42).build());
+        contract.launch(launch_config("ART"));
+        let transfers = transfers();
+        assert!(transfers.contains(&(accounts(1), 42)));
+        assert!(transfers.contains(&(
+            "art.launchpad.near".parse().unwrap(),
+            internal_account_cost(Template::Classic, NFT_CODE.len())
+        )));
+    }
+
+    #[test]
+    fn launch_with_the_exact_deposit_refunds_nothing() {
+        let mut contract = setup();
+        let quote = contract.quote_launch(accounts(1), launch_config("ART")).0;
+
+        testing_env!(context(accounts(1), quote).build());
+        contract.launch(launch_config("ART"));
+        assert!(transfers().iter().all(|(receiver_id, _)| receiver_id != &accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be at least")]
+    fn launch_requires_the_quoted_deposit() {
+        let mut contract = setup();
+        let quote = contract.quote_launch(accounts(1), launch_config("ART")).0;
+
+        testing_env!(context(accounts(1), quote - 1).build());
+        contract.launch(launch_config("ART"));
+    }
+
+    fn resolve_launch(contract: &mut Contract, collection: Collection, result: PromiseResult) -> bool {
+        testing_env!(
+            context("launchpad.near".parse().unwrap(), 0).build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        contract.resolve_launch(collection, U128(0), U128(0), None)
+    }
+
+    /// Start launching ART for `accounts(1)` and return the collection it deploys.
+    fn start_launch(contract: &mut Contract) -> Collection {
+        let (collection, _, cost) = contract.internal_prepare_launch(&accounts(1), &launch_config("ART"));
+        testing_env!(context(accounts(1), cost).build());
+        contract.launch(launch_config("ART"));
+        collection
+    }
+
+    #[test]
+    fn pending_launch_reserves_its_symbol_and_prefix() {
+        let mut contract = setup();
+        start_launch(&mut contract);
+
+        assert!(!contract.is_symbol_available("art".to_string(), Some("art-2".to_string())));
+        assert!(!contract.is_symbol_available("OTHER".to_string(), Some("art".to_string())));
+        assert!(contract.get_collection("ART".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "A collection with symbol art was already launched")]
+    fn concurrent_launch_of_the_same_symbol_is_rejected() {
+        let mut contract = setup();
+        start_launch(&mut contract);
+
+        let mut config = launch_config("art");
+        config.prefix = Some("art-2".to_string());
+        contract.launch(config);
+    }
+
+    #[test]
+    fn failed_launch_releases_its_symbol_and_prefix() {
+        let mut contract = setup();
+        let collection = start_launch(&mut contract);
+
+        assert!(!resolve_launch(&mut contract, collection, PromiseResult::Failed));
+        assert!(contract.is_symbol_available("ART".to_string(), None));
+        assert!(contract.get_collection("ART".to_string()).is_none());
+    }
+
+    #[test]
+    fn successful_launch_keeps_its_symbol_and_prefix() {
+        let mut contract = setup();
+        let collection = start_launch(&mut contract);
+
+        assert!(resolve_launch(&mut contract, collection, PromiseResult::Successful(vec![])));
+        assert!(!contract.pending_prefixes.contains(&"art".to_string()));
+        assert!(!contract.is_symbol_available("OTHER".to_string(), Some("art".to_string())));
+        assert_eq!(
+            contract.get_collection("ART".to_string()).unwrap().collection_id.as_str(),
+            "art.launchpad.near"
+        );
+    }
+
+    #[test]
+    fn failed_launch_refunds_the_cost() {
+        let mut contract = setup();
+        let (collection, _, cost) = contract.internal_prepare_launch(&accounts(1), &launch_config("ART"));
+
+        testing_env!(
+            context("launchpad.near".parse().unwrap(), 0).build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_launch(collection, U128(cost), U128(0), None));
+        assert_eq!(transfers(), vec![(accounts(1), cost)]);
+        assert!(contract.get_collection("ART".to_string()).is_none());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::*;
+
+/// Moderation state of a collection in the registry.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionStatus {
+    Active,
+    //still listed, but marked by a moderator for review
+    Flagged,
+    //hidden from listings by a moderator
+    Delisted,
+    //minting stopped by the owner, holders can redeem until the redeem deadline
+    Closed,
+    //closed and its unclaimed rewards swept after the redeem deadline
+    Swept,
+}
+
+/// A collection launched through the launchpad, as recorded once its deployment succeeded.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Collection {
+    pub creator_id: AccountId,
+    pub collection_id: AccountId,
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: Option<String>,
+    pub total_supply: U128,
+    pub mint_price: U128,
+    pub mint_currency: Option<AccountId>,
+    pub payment_split_percent: U128,
+    pub burn_fee: U128,
+    pub sale_start: Option<U64>,
+    pub sale_end: Option<U64>,
+    pub template: Template,
+    //stored NFT code version the collection was deployed with
+    pub code_version: String,
+    //block timestamp (in nanoseconds) of the launch
+    pub launched_at: U64,
+    //outcome of the last code upgrade pushed through `upgrade_collection`
+    pub last_upgrade: Option<CollectionUpgrade>,
+    pub status: CollectionStatus,
+    //nanosecond timestamp until which holders of a closed collection can redeem
+    pub redeem_deadline: Option<U64>,
+    //unclaimed rewards swept from a closed collection
+    pub swept_amount: Option<U128>,
+    //hidden from listings by a moderator, which outlasts closing the collection
+    #[serde(default)]
+    pub delisted: bool,
+}
+
+impl Collection {
+    /// Key of the collection in the registry: the sub-account prefix of its account.
+    pub(crate) fn registry_key(&self) -> String {
+        let current_id = env::current_account_id();
+        self.collection_id
+            .as_str()
+            .strip_suffix(&format!(".{}", current_id))
+            .unwrap_or(self.collection_id.as_str())
+            .to_string()
+    }
+}
+
+//trie records, index entries and the creator's set that a registry entry adds on top of
+//the serialized collection itself
+const COLLECTION_ENTRY_OVERHEAD: u128 = 500;
+
+/// Storage cost of recording `collection` in the registry.
+pub(crate) fn internal_collection_storage_cost(collection: &Collection) -> u128 {
+    let key_bytes = collection.registry_key().len() as u128;
+    let symbol_bytes = collection.symbol.len() as u128;
+    let value_bytes = near_sdk::borsh::to_vec(collection).unwrap().len() as u128;
+    NEAR_PER_STORAGE * (COLLECTION_ENTRY_OVERHEAD + 4 * key_bytes + symbol_bytes + value_bytes)
+}
+
+impl Contract {
+    /// The collection deployed to the predecessor account, for calls collections make.
+    fn internal_calling_collection(&self) -> (String, Collection) {
+        let collection_id = env::predecessor_account_id();
+        let key = collection_id
+            .as_str()
+            .strip_suffix(&format!(".{}", env::current_account_id()))
+            .unwrap_or_else(|| env::panic_str("Only collections can call this method"))
+            .to_string();
+        let collection = self
+            .collections
+            .get(&key)
+            .filter(|collection| collection.collection_id == collection_id)
+            .unwrap_or_else(|| env::panic_str("Only collections can call this method"));
+        (key, collection)
+    }
+
+    /// The registry key of the collection launched under `symbol`, if any.
+    pub(crate) fn internal_collection_key(&self, symbol: &str) -> Option<String> {
+        self.collection_prefixes.get(&symbol.to_lowercase())
+    }
+
+    /// Reserve the symbol and the prefix of a collection being deployed.
+    pub(crate) fn internal_reserve_launch(&mut self, collection: &Collection) {
+        let key = collection.registry_key();
+        self.collection_prefixes.insert(&collection.symbol.to_lowercase(), &key);
+        self.pending_prefixes.insert(&key);
+    }
+
+    /// Release the reservation of `internal_reserve_launch` once the deployment resolved. The
+    /// symbol stays taken if it `succeeded`.
+    pub(crate) fn internal_release_launch(&mut self, collection: &Collection, succeeded: bool) {
+        let key = collection.registry_key();
+        self.pending_prefixes.remove(&key);
+        if !succeeded {
+            self.collection_prefixes.remove(&collection.symbol.to_lowercase());
+        }
+    }
+
+    /// Record a launched collection in the registry and index it under its creator and its
+    /// symbol.
+    pub(crate) fn internal_add_collection(&mut self, collection: Collection) {
+        let key = collection.registry_key();
+        self.collections.insert(&key, &collection);
+        self.collection_prefixes.insert(&collection.symbol.to_lowercase(), &key);
+
+        let mut by_creator = self
+            .collections_by_creator
+            .get(&collection.creator_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::CollectionsByCreatorInner {
+                    account_hash: env::sha256_array(collection.creator_id.as_bytes()),
+                })
+            });
+        by_creator.insert(&key);
+        self.collections_by_creator.insert(&collection.creator_id, &by_creator);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Flag, delist or reinstate the collection launched under `symbol`. Only moderators can
+    /// call it. A closed collection keeps its closure status and can only be delisted or
+    /// listed again.
+    pub fn set_collection_status(&mut self, symbol: String, status: CollectionStatus, reason: Option<String>) {
+        self.assert_role(Role::Moderator);
+        require!(
+            !matches!(status, CollectionStatus::Closed | CollectionStatus::Swept),
+            "Collections can only be closed by their owner"
+        );
+        let key = self
+            .internal_collection_key(&symbol)
+            .unwrap_or_else(|| env::panic_str("Collection not found"));
+        let mut collection = self
+            .collections
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("Collection not found"));
+        if !matches!(collection.status, CollectionStatus::Closed | CollectionStatus::Swept) {
+            collection.status = status;
+        }
+        collection.delisted = status == CollectionStatus::Delisted;
+        self.collections.insert(&key, &collection);
+
+        Event::SetCollectionStatus {
+            collection_id: &collection.collection_id,
+            status,
+            moderator_id: &env::predecessor_account_id(),
+            reason: reason.as_ref(),
+        }
+        .emit();
+    }
+
+    /// Called by a collection when its owner closes it.
+    pub fn on_collection_closed(&mut self, redeem_deadline: U64) {
+        let (key, mut collection) = self.internal_calling_collection();
+        collection.status = CollectionStatus::Closed;
+        collection.redeem_deadline = Some(redeem_deadline);
+        self.collections.insert(&key, &collection);
+
+        Event::CloseCollection {
+            collection_id: &collection.collection_id,
+            redeem_deadline,
+        }
+        .emit();
+    }
+
+    /// Called by a closed collection when its unclaimed rewards are swept.
+    pub fn on_collection_swept(&mut self, amount: U128) {
+        let (key, mut collection) = self.internal_calling_collection();
+        require!(collection.status == CollectionStatus::Closed, "Collection is not closed");
+        collection.status = CollectionStatus::Swept;
+        collection.swept_amount = Some(amount);
+        self.collections.insert(&key, &collection);
+
+        Event::SweepCollection {
+            collection_id: &collection.collection_id,
+            amount: &amount,
+        }
+        .emit();
+    }
+
+    /// Get the collection launched under `symbol`, if any, whatever its sub-account prefix.
+    pub fn get_collection(&self, symbol: String) -> Option<Collection> {
+        self.internal_collection_key(&symbol)
+            .and_then(|key| self.collections.get(&key))
+    }
+
+    /// Total number of collections launched through this contract.
+    pub fn get_collections_count(&self) -> U128 {
+        U128(self.collections.len() as u128)
+    }
+
+    /// Paginate through every launched collection that isn't delisted, in launch order.
+    pub fn get_collections(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Collection> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        require!(
+            (self.collections.len() as u128) >= start,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+
+        self.collections
+            .values()
+            .filter(|collection| !collection.delisted)
+            .skip(start as usize)
+            .take(limit)
+            .collect()
+    }
+
+    /// Number of collections launched by `creator_id`.
+    pub fn get_collections_count_by_creator(&self, creator_id: AccountId) -> U128 {
+        U128(
+            self.collections_by_creator
+                .get(&creator_id)
+                .map(|symbols| symbols.len() as u128)
+                .unwrap_or(0),
+        )
+    }
+
+    /// Paginate through the collections launched by `creator_id` that aren't delisted.
+    pub fn get_collections_by_creator(
+        &self,
+        creator_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Collection> {
+        let symbols = if let Some(symbols) = self.collections_by_creator.get(&creator_id) {
+            symbols
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        require!(
+            (symbols.len() as u128) >= start,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+
+        symbols
+            .iter()
+            .filter_map(|symbol| self.collections.get(&symbol))
+            .filter(|collection| !collection.delisted)
+            .skip(start as usize)
+            .take(limit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{context, launch_config, setup};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    /// `setup()` with the collection ART of `accounts(1)` in the registry.
+    fn setup_with_collection() -> Contract {
+        let mut contract = setup();
+        let (collection, _, _) = contract.internal_prepare_launch(&accounts(1), &launch_config("ART"));
+        contract.internal_add_collection(collection);
+        contract
+    }
+
+    fn close(contract: &mut Contract) {
+        testing_env!(context("art.launchpad.near".parse().unwrap(), 0).build());
+        contract.on_collection_closed(U64(1_000));
+    }
+
+    fn moderate(contract: &mut Contract, status: CollectionStatus) {
+        testing_env!(context(accounts(0), 0).build());
+        contract.set_collection_status("ART".to_string(), status, None);
+    }
+
+    fn listed(contract: &Contract) -> bool {
+        !contract.get_collections(None, None).is_empty()
+            && !contract.get_collections_by_creator(accounts(1), None, None).is_empty()
+    }
+
+    #[test]
+    fn delisted_collections_are_hidden_until_reinstated() {
+        let mut contract = setup_with_collection();
+        moderate(&mut contract, CollectionStatus::Delisted);
+        assert!(contract.get_collections(None, None).is_empty());
+        assert!(contract.get_collections_by_creator(accounts(1), None, None).is_empty());
+
+        moderate(&mut contract, CollectionStatus::Active);
+        assert!(listed(&contract));
+    }
+
+    #[test]
+    fn delisted_then_closed_stays_hidden() {
+        let mut contract = setup_with_collection();
+        moderate(&mut contract, CollectionStatus::Delisted);
+        close(&mut contract);
+
+        let collection = contract.get_collection("ART".to_string()).unwrap();
+        assert_eq!(collection.status, CollectionStatus::Closed);
+        assert!(collection.delisted);
+        assert!(contract.get_collections(None, None).is_empty());
+        assert!(contract.get_collections_by_creator(accounts(1), None, None).is_empty());
+    }
+
+    #[test]
+    fn closed_collections_can_still_be_moderated() {
+        let mut contract = setup_with_collection();
+        close(&mut contract);
+        moderate(&mut contract, CollectionStatus::Delisted);
+        assert!(!listed(&contract));
+
+        moderate(&mut contract, CollectionStatus::Active);
+        assert!(listed(&contract));
+        assert_eq!(contract.get_collection("ART".to_string()).unwrap().status, CollectionStatus::Closed);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::*;
+
+/// Roles the owner and admins can grant. The owner implicitly holds every role.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    //manages code versions, moderators and approved creators
+    Admin,
+    //flags and delists collections
+    Moderator,
+    //can launch collections when the launchpad is in allowlisted mode
+    Creator,
+}
+
+/// Who is allowed to launch collections.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchMode {
+    Permissionless,
+    AllowlistedCreators,
+}
+
+impl Contract {
+    pub(crate) fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the owner can call this method"
+        );
+    }
+
+    pub(crate) fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        account_id == &self.owner_id
+            || self
+                .role_members
+                .get(&role)
+                .map(|members| members.contains(account_id))
+                .unwrap_or(false)
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        require!(
+            self.has_role(&env::predecessor_account_id(), role),
+            format!("Only accounts with the {:?} role can call this method", role)
+        );
+    }
+
+    fn assert_can_manage(&self, role: Role) {
+        if role == Role::Admin {
+            self.assert_owner();
+        } else {
+            self.assert_role(Role::Admin);
+        }
+    }
+
+    pub(crate) fn assert_can_launch(&self, creator_id: &AccountId) {
+        if self.launch_mode == LaunchMode::AllowlistedCreators {
+            require!(
+                self.has_role(creator_id, Role::Creator),
+                "Only approved creators can launch collections"
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grant `role` to `account_id`. The owner grants admins; admins grant the other roles.
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_can_manage(role);
+
+        let mut members = self.role_members.get(&role).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::RoleMembersInner { role })
+        });
+        if members.insert(&account_id) {
+            self.role_members.insert(&role, &members);
+            Event::GrantRole { role, account_id: &account_id }.emit();
+        }
+    }
+
+    /// Revoke `role` from `account_id`, with the same permissions as `grant_role`.
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_can_manage(role);
+
+        if let Some(mut members) = self.role_members.get(&role) {
+            if members.remove(&account_id) {
+                self.role_members.insert(&role, &members);
+                Event::RevokeRole { role, account_id: &account_id }.emit();
+            }
+        }
+    }
+
+    pub fn has_role_of(&self, role: Role, account_id: AccountId) -> bool {
+        self.has_role(&account_id, role)
+    }
+
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members
+            .get(&role)
+            .map(|members| members.to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn set_launch_mode(&mut self, mode: LaunchMode) {
+        self.assert_role(Role::Admin);
+        self.launch_mode = mode;
+    }
+
+    pub fn get_launch_mode(&self) -> LaunchMode {
+        self.launch_mode
+    }
+
+    /// First step of an ownership transfer. `new_owner_id` has to call `accept_ownership`;
+    /// passing `None` cancels a pending proposal.
+    pub fn propose_owner(&mut self, new_owner_id: Option<AccountId>) {
+        self.assert_owner();
+        self.proposed_owner_id = new_owner_id;
+    }
+
+    /// Second step of an ownership transfer, called by the proposed owner.
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        require!(
+            self.proposed_owner_id.as_ref() == Some(&caller),
+            "Only the proposed owner can accept ownership"
+        );
+        Event::TransferOwnership {
+            old_owner_id: &self.owner_id,
+            new_owner_id: &caller,
+        }
+        .emit();
+        self.owner_id = caller;
+        self.proposed_owner_id = None;
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_proposed_owner(&self) -> Option<AccountId> {
+        self.proposed_owner_id.clone()
+    }
+}
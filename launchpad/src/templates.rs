@@ -0,0 +1,79 @@
+use crate::*;
+
+/// Kinds of contracts the launchpad can deploy. Each template has its own stored code
+/// versions and its own arguments for the contract's `new`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Template {
+    //every token gets a vault holding its share of the mint price
+    #[default]
+    Classic,
+    //plain NFTs without vaults, the whole mint price goes to the owner
+    Plain,
+    //vault-backed tokens that can't be transferred
+    Soulbound,
+    //unlimited supply minted during a sale window
+    OpenEdition,
+    //NEP-141 fungible token deployed by `launch_ft`
+    FungibleToken,
+}
+
+impl Template {
+    /// Check the parts of `config` that this template constrains.
+    pub(crate) fn assert_valid_config(&self, config: &LaunchConfig) {
+        match self {
+            Template::Classic | Template::Soulbound => {}
+            Template::Plain => {
+                require!(
+                    config.payment_split_percent.0 == 0 && config.burn_fee.0 == 0,
+                    "Plain collections have no vaults, payment_split_percent and burn_fee must be 0"
+                );
+            }
+            Template::OpenEdition => {
+                require!(
+                    config.total_supply.0 == 0,
+                    "Open editions have an unlimited supply, total_supply must be 0"
+                );
+                require!(config.sale_end.is_some(), "Open editions need a sale_end");
+            }
+            Template::FungibleToken => env::panic_str("Fungible tokens are launched with launch_ft"),
+        }
+    }
+
+    /// Arguments of the NFT contract's `new` for a collection of this template.
+    pub(crate) fn init_args(
+        &self,
+        owner_id: &AccountId,
+        config: &LaunchConfig,
+        platform_fee_bps: U128,
+    ) -> near_sdk::serde_json::Value {
+        let mut args = json!({
+            "owner_id": owner_id.to_string(),
+            "metadata": config.metadata,
+            "total_supply": config.total_supply,
+            "mint_price": config.mint_price,
+            "mint_currency": config.mint_currency,
+            "payment_split_percent": config.payment_split_percent,
+            "burn_fee": config.burn_fee,
+            "platform_fee_bps": platform_fee_bps,
+            "sale_start": config.sale_start,
+            "sale_end": config.sale_end,
+            "provenance_hash": config.provenance_hash,
+            "placeholder_uri": config.placeholder_uri,
+        });
+
+        match self {
+            Template::Classic | Template::OpenEdition => {}
+            Template::Plain => {
+                args["vaults_enabled"] = json!(false);
+            }
+            Template::Soulbound => {
+                args["soulbound"] = json!(true);
+            }
+            Template::FungibleToken => env::panic_str("Fungible tokens are launched with launch_ft"),
+        }
+        args
+    }
+}
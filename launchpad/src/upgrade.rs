@@ -0,0 +1,100 @@
+use crate::*;
+
+const GAS_FOR_UPDATE_CODE: Gas = Gas::from_tgas(150);
+const GAS_FOR_RESOLVE_UPGRADE: Gas = Gas::from_tgas(10);
+
+/// Outcome of the last upgrade pushed to a collection.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionUpgrade {
+    pub from_version: String,
+    pub to_version: String,
+    pub succeeded: bool,
+    //block timestamp (in nanoseconds) of the upgrade
+    pub upgraded_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Push the stored NFT code `version` to the collection launched under `symbol`.
+    /// Can be called by the collection creator or an admin. The collection owner
+    /// has to opt in first by calling `authorize_upgrade` on the collection with the code hash
+    /// of `version`, after which the collection deploys the code and runs its `migrate`.
+    /// Only versions stored after the one the collection runs are accepted.
+    pub fn upgrade_collection(&mut self, symbol: String, version: String) -> Promise {
+        let collection = self
+            .get_collection(symbol)
+            .unwrap_or_else(|| env::panic_str("Collection not found"));
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == collection.creator_id || self.has_role(&caller, Role::Admin),
+            "Only the collection creator or an admin can upgrade a collection"
+        );
+        let current = self
+            .nft_codes
+            .get(&collection.code_version)
+            .unwrap_or_else(|| env::panic_str("Code version of the collection is not stored"));
+        let target = self
+            .nft_codes
+            .get(&version)
+            .unwrap_or_else(|| env::panic_str(&format!("NFT code version {} is not stored", version)));
+        require!(
+            target.stored_at.0 > current.stored_at.0,
+            format!("Version {} is not newer than {}", version, collection.code_version)
+        );
+        require!(
+            env::prepaid_gas() >= GAS_FOR_UPDATE_CODE.saturating_add(GAS_FOR_RESOLVE_UPGRADE),
+            "Not enough gas attached to upgrade the collection"
+        );
+        let (version, code) = self.internal_nft_code(collection.template, Some(version));
+        let key = collection.registry_key();
+
+        Promise::new(collection.collection_id)
+            .function_call(
+                "update_code".to_string(),
+                code,
+                NearToken::from_yoctonear(0),
+                GAS_FOR_UPDATE_CODE,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_UPGRADE)
+                    .resolve_upgrade(key, version),
+            )
+    }
+
+    /// Callback of `upgrade_collection`. Records the outcome on the collection and moves it to
+    /// the new version if the deployment and migration succeeded.
+    #[private]
+    pub fn resolve_upgrade(&mut self, key: String, version: String) -> bool {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let mut collection = self
+            .collections
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("Collection not found"));
+
+        let upgrade = CollectionUpgrade {
+            from_version: collection.code_version.clone(),
+            to_version: version,
+            succeeded,
+            upgraded_at: U64(env::block_timestamp()),
+        };
+
+        Event::UpgradeCollection {
+            collection_id: &collection.collection_id,
+            from_version: &upgrade.from_version,
+            to_version: &upgrade.to_version,
+            succeeded,
+        }
+        .emit();
+
+        if succeeded {
+            collection.code_version = upgrade.to_version.clone();
+        }
+        collection.last_upgrade = Some(upgrade);
+        self.collections.insert(&key, &collection);
+
+        succeeded
+    }
+}
@@ -0,0 +1,238 @@
+use crate::*;
+
+//the longest account ID NEAR accepts
+const MAX_ACCOUNT_ID_LEN: usize = 64;
+
+/// Why `prefix` can't be used as a single sub-account segment, if it can't. A segment is
+/// made of lowercase letters and digits, optionally separated by single `-` or `_`, with no
+/// leading or trailing separator, and has to keep the full account ID within 64 characters.
+fn account_segment_error(prefix: &str) -> Option<String> {
+    if prefix.is_empty() {
+        return Some("Collection prefix can't be empty".to_string());
+    }
+
+    let mut last_was_separator = true;
+    for c in prefix.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => last_was_separator = false,
+            '-' | '_' => {
+                if last_was_separator {
+                    return Some(format!("Collection prefix {} can't start with or repeat a separator", prefix));
+                }
+                last_was_separator = true;
+            }
+            _ => {
+                return Some(format!(
+                    "Collection prefix {} can only contain lowercase letters, digits, '-' and '_'",
+                    prefix
+                ))
+            }
+        }
+    }
+    if last_was_separator {
+        return Some(format!("Collection prefix {} can't end with a separator", prefix));
+    }
+
+    let max_len = MAX_ACCOUNT_ID_LEN.saturating_sub(env::current_account_id().as_str().len() + 1);
+    if prefix.len() > max_len {
+        return Some(format!("Collection prefix {} is longer than {} characters", prefix, max_len));
+    }
+    None
+}
+
+/// Sub-account prefix of a collection: `prefix` when given, otherwise its lowercase symbol.
+pub(crate) fn collection_prefix(symbol: &str, prefix: Option<&String>) -> String {
+    prefix.cloned().unwrap_or_else(|| symbol.to_lowercase())
+}
+
+/// Account a collection with the sub-account `prefix` is deployed to.
+pub(crate) fn collection_account_id(prefix: &str) -> AccountId {
+    format!("{}.{}", prefix, env::current_account_id())
+        .parse()
+        .unwrap_or_else(|_| env::panic_str(&format!("Collection prefix {} is not valid", prefix)))
+}
+
+impl Contract {
+    /// Validate a launch configuration before any promise is created.
+    pub(crate) fn assert_valid_launch(&self, config: &LaunchConfig) {
+        let LaunchConfig {
+            metadata,
+            mint_price,
+            mint_currency,
+            payment_split_percent,
+            burn_fee,
+            sale_start,
+            sale_end,
+            provenance_hash,
+            placeholder_uri,
+            ..
+        } = config;
+
+        metadata.assert_valid();
+        config.template.assert_valid_config(config);
+        require!(
+            payment_split_percent.0 <= 100,
+            "payment_split_percent can't be more than 100"
+        );
+        require!(burn_fee.0 <= 100, "burn_fee can't be more than 100");
+        require!(
+            payment_split_percent.0 * 100 + self.platform_fee.mint_fee_bps.0 <= 10_000,
+            "payment_split_percent leaves no room for the platform mint fee"
+        );
+        if mint_currency.is_some() {
+            require!(
+                mint_price.0 > 0,
+                "mint_price has to be greater than 0 when a mint_currency is set"
+            );
+        }
+
+        if let (Some(start), Some(end)) = (sale_start, sale_end) {
+            require!(start.0 < end.0, "sale_start has to be before sale_end");
+        }
+        if let Some(end) = sale_end {
+            require!(end.0 > env::block_timestamp(), "sale_end has to be in the future");
+        }
+        require!(
+            placeholder_uri.is_none() || provenance_hash.is_some(),
+            "placeholder_uri needs a provenance_hash"
+        );
+
+        self.assert_symbol_available(&metadata.symbol);
+        self.assert_prefix_available(&collection_prefix(&metadata.symbol, config.prefix.as_ref()));
+    }
+
+    /// Check that no launched or pending collection uses `symbol` yet, whatever its prefix.
+    pub(crate) fn assert_symbol_available(&self, symbol: &str) {
+        require!(
+            self.internal_collection_key(symbol).is_none(),
+            format!("A collection with symbol {} was already launched", symbol)
+        );
+    }
+
+    /// Check that `prefix` is a valid sub-account segment that no launched or pending
+    /// collection uses yet.
+    pub(crate) fn assert_prefix_available(&self, prefix: &str) {
+        if let Some(error) = account_segment_error(prefix) {
+            env::panic_str(&error);
+        }
+        let prefix = prefix.to_string();
+        require!(
+            self.collections.get(&prefix).is_none() && !self.pending_prefixes.contains(&prefix),
+            format!("A collection with prefix {} was already launched", prefix)
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Account a collection with `symbol` would be deployed to, or with `prefix` when it
+    /// overrides the sub-account prefix. Panics if the prefix is not a valid sub-account.
+    pub fn predict_collection_id(&self, symbol: String, prefix: Option<String>) -> AccountId {
+        let prefix = collection_prefix(&symbol, prefix.as_ref());
+        if let Some(error) = account_segment_error(&prefix) {
+            env::panic_str(&error);
+        }
+        collection_account_id(&prefix)
+    }
+
+    /// Whether a collection with `symbol` could be launched, with `prefix` when it overrides
+    /// the sub-account prefix: no launched collection uses the symbol or the prefix, and the
+    /// prefix is valid.
+    pub fn is_symbol_available(&self, symbol: String, prefix: Option<String>) -> bool {
+        let prefix = collection_prefix(&symbol, prefix.as_ref());
+        self.internal_collection_key(&symbol).is_none()
+            && account_segment_error(&prefix).is_none()
+            && self.collections.get(&prefix).is_none()
+            && !self.pending_prefixes.contains(&prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{launch_config, setup};
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn accepts_a_valid_launch() {
+        let contract = setup();
+        contract.assert_valid_launch(&launch_config("ART"));
+    }
+
+    #[test]
+    #[should_panic(expected = "payment_split_percent can't be more than 100")]
+    fn rejects_a_split_above_100() {
+        let contract = setup();
+        let mut config = launch_config("ART");
+        config.payment_split_percent = U128(101);
+        contract.assert_valid_launch(&config);
+    }
+
+    #[test]
+    fn accepts_a_split_and_burn_fee_of_100() {
+        let contract = setup();
+        let mut config = launch_config("ART");
+        config.payment_split_percent = U128(100);
+        config.burn_fee = U128(100);
+        contract.assert_valid_launch(&config);
+    }
+
+    #[test]
+    #[should_panic(expected = "burn_fee can't be more than 100")]
+    fn rejects_a_burn_fee_above_100() {
+        let contract = setup();
+        let mut config = launch_config("ART");
+        config.burn_fee = U128(101);
+        contract.assert_valid_launch(&config);
+    }
+
+    #[test]
+    #[should_panic(expected = "mint_price has to be greater than 0")]
+    fn rejects_a_free_mint_in_a_currency() {
+        let contract = setup();
+        let mut config = launch_config("ART");
+        config.mint_currency = Some("usdc.near".parse().unwrap());
+        config.mint_price = U128(0);
+        contract.assert_valid_launch(&config);
+    }
+
+    #[test]
+    fn checks_prefixes_are_account_segments() {
+        setup();
+        assert_eq!(account_segment_error("art_2-x"), None);
+        for prefix in ["", "art.x", "art x", "-art", "art-", "art--x", "art_-x"] {
+            assert!(account_segment_error(prefix).is_some(), "{} was accepted", prefix);
+        }
+    }
+
+    #[test]
+    fn bounds_the_prefix_by_the_account_id_length() {
+        // "<prefix>.launchpad.near" has to fit in 64 characters
+        let max_len = MAX_ACCOUNT_ID_LEN - "launchpad.near".len() - 1;
+        let contract = setup();
+        contract.assert_valid_launch(&launch_config(&"a".repeat(max_len)));
+        assert!(!contract.is_symbol_available("a".repeat(max_len + 1), None));
+    }
+
+    #[test]
+    #[should_panic(expected = "A collection with symbol art was already launched")]
+    fn rejects_a_duplicate_symbol_under_another_prefix() {
+        let mut contract = setup();
+        let (collection, _, _) = contract.internal_prepare_launch(&accounts(1), &launch_config("ART"));
+        contract.internal_add_collection(collection);
+
+        let mut config = launch_config("art");
+        config.prefix = Some("art-2".to_string());
+        contract.assert_valid_launch(&config);
+    }
+
+    #[test]
+    #[should_panic(expected = "sale_start has to be before sale_end")]
+    fn rejects_a_sale_ending_before_it_starts() {
+        let contract = setup();
+        let mut config = launch_config("ART");
+        config.sale_start = Some(U64(2_000));
+        config.sale_end = Some(U64(1_000));
+        contract.assert_valid_launch(&config);
+    }
+}
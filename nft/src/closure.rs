@@ -0,0 +1,135 @@
+use crate::*;
+
+const GAS_FOR_NOTIFY_LAUNCHPAD: Gas = Gas::from_tgas(10);
+
+#[near_bindgen]
+impl Contract {
+    /// Permanently stop minting. Holders can still burn their tokens to redeem their vaults
+    /// and withdraw their rewards until `redeem_deadline` (nanosecond timestamp); after it the
+    /// owner can sweep the unclaimed rewards to `sweep_account_id` (the owner when `None`).
+    pub fn close_collection(&mut self, redeem_deadline: U64, sweep_account_id: Option<AccountId>) {
+        self.assert_collection_owner();
+        require!(self.closed_at.is_none(), "Collection is already closed");
+        require!(
+            redeem_deadline.0 > env::block_timestamp(),
+            "Redeem deadline has to be in the future"
+        );
+
+        let sweep_account_id = sweep_account_id.unwrap_or_else(|| self.tokens.owner_id.clone());
+        self.closed_at = Some(env::block_timestamp());
+        self.redeem_deadline = Some(redeem_deadline.0);
+        self.sweep_account_id = Some(sweep_account_id.clone());
+
+        Event::CloseCollection {
+            owner_id: &self.tokens.owner_id,
+            redeem_deadline,
+            sweep_account_id: &sweep_account_id,
+        }
+        .emit();
+
+        Promise::new(self.factory_id.clone()).function_call(
+            "on_collection_closed".to_string(),
+            json!({
+                "redeem_deadline": redeem_deadline,
+            }).to_string().into_bytes().to_vec(),
+            NearToken::from_yoctonear(0),
+            GAS_FOR_NOTIFY_LAUNCHPAD,
+        );
+    }
+
+    /// Send every reward holders didn't withdraw before the redeem deadline to the sweep
+    /// account. Only the owner can call it, once.
+    pub fn sweep_unclaimed(&mut self) {
+        self.assert_collection_owner();
+        let redeem_deadline = self
+            .redeem_deadline
+            .unwrap_or_else(|| env::panic_str("Collection is not closed"));
+        require!(
+            env::block_timestamp() >= redeem_deadline,
+            "Redeem deadline has not passed yet"
+        );
+        require!(!self.swept, "Unclaimed rewards were already swept");
+
+        let sweep_account_id = self.sweep_account_id.clone().unwrap();
+        let amount = self.unclaimed_balance;
+        self.unclaimed_balance = 0;
+        self.swept = true;
+
+        if amount > 0 {
+            if let Some(ft_id) = self.mint_currency.clone() {
+                Promise::new(ft_id.clone()).function_call(
+                    "storage_deposit".to_string(),
+                    json!({
+                        "account_id": sweep_account_id.to_string()
+                    }).to_string().into_bytes().to_vec(),
+                    NearToken::from_millinear(30),
+                    Gas::from_tgas(20),
+                );
+
+                Promise::new(ft_id).function_call(
+                    "ft_transfer".to_string(),
+                    json!({
+                        "receiver_id": sweep_account_id.to_string(),
+                        "amount": amount.to_string(),
+                    }).to_string().into_bytes().to_vec(),
+                    NearToken::from_yoctonear(1),
+                    Gas::from_tgas(20),
+                );
+            } else {
+                Promise::new(sweep_account_id.clone()).transfer(NearToken::from_yoctonear(amount));
+            }
+        }
+
+        Event::SweepUnclaimed {
+            sweep_account_id: &sweep_account_id,
+            amount: &U128(amount),
+        }
+        .emit();
+
+        Promise::new(self.factory_id.clone()).function_call(
+            "on_collection_swept".to_string(),
+            json!({
+                "amount": U128(amount),
+            }).to_string().into_bytes().to_vec(),
+            NearToken::from_yoctonear(0),
+            GAS_FOR_NOTIFY_LAUNCHPAD,
+        );
+    }
+
+    /// Closing time, redeem deadline (nanosecond timestamps) and sweep account, if the
+    /// collection was closed.
+    pub fn closure(&self) -> Option<(U64, U64, AccountId)> {
+        self.closed_at.map(|closed_at| {
+            (
+                U64(closed_at),
+                U64(self.redeem_deadline.unwrap()),
+                self.sweep_account_id.clone().unwrap(),
+            )
+        })
+    }
+
+    /// Rewards credited to holders and not withdrawn yet.
+    pub fn unclaimed_balance(&self) -> U128 {
+        U128(self.unclaimed_balance)
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_collection_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.tokens.owner_id,
+            "Only the collection owner can call this method"
+        );
+    }
+
+    /// Holders can redeem vaults and withdraw rewards until the redeem deadline of a closed
+    /// collection.
+    pub(crate) fn assert_redeemable(&self) {
+        if let Some(redeem_deadline) = self.redeem_deadline {
+            require!(
+                env::block_timestamp() < redeem_deadline,
+                "Redeem deadline of this closed collection has passed"
+            );
+        }
+    }
+}
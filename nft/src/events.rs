@@ -0,0 +1,78 @@
+use crate::*;
+use near_sdk::serde::Serialize;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum Event<'a> {
+    CloseCollection {
+        owner_id: &'a AccountId,
+        redeem_deadline: U64,
+        sweep_account_id: &'a AccountId,
+    },
+    SweepUnclaimed {
+        sweep_account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    Reveal {
+        base_uri: &'a String,
+        starting_index: U64,
+        provenance_hash: &'a String,
+    },
+    FtDeposit {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    FtMintPayment {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    FtMintRefund {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    FtWithdraw {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    FtWithdrawFailed {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    NearDeposit {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    NearWithdraw {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+    },
+}
+
+impl Event<'_> {
+    pub fn emit(&self) {
+        emit_event(&self);
+    }
+}
+
+const EVENT_STANDARD: &str = "linear";
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+// Emit event that follows NEP-297 standard: https://nomicon.io/Standards/EventsFormat
+// Arguments
+// * `standard`: name of standard, e.g. nep171
+// * `version`: e.g. 1.0.0
+// * `event`: type of the event, e.g. nft_mint
+// * `data`: associate event data. Strictly typed for each set {standard, version, event} inside corresponding NEP
+pub(crate) fn emit_event<T: ?Sized + Serialize>(data: &T) {
+    let result = json!(data);
+    let event_json = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_STANDARD_VERSION,
+        "event": result["event"],
+        "data": [result["data"]]
+    })
+    .to_string();
+    log!(format!("EVENT_JSON:{}", event_json));
+}
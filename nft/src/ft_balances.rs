@@ -1,10 +1,33 @@
 use crate::*;
 
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(20);
+
+/// What an `ft_transfer_call` to the collection asks for besides depositing. An empty `msg`
+/// only deposits.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum FtTransferMsg {
+    //mint `quantity` tokens with contract-assigned ids to `token_owner_id` (the sender when
+    //`None`), paying the NEAR costs from the sender's `near_deposit`
+    Mint {
+        token_owner_id: Option<AccountId>,
+        #[serde(default = "default_quantity")]
+        quantity: u64,
+        allowlist_proof: Option<AllowlistProof>,
+    },
+}
+
+fn default_quantity() -> u64 {
+    1
+}
+
 trait FungibleTokenReceiver {
     fn ft_on_transfer(
         &mut self,
         sender_id: AccountId,
         amount: U128,
+        msg: String,
     ) -> U128;
 }
 
@@ -14,40 +37,428 @@ impl FungibleTokenReceiver for Contract {
         &mut self,
         sender_id: AccountId,
         amount: U128,
+        msg: String,
     ) -> U128 {
-        // get the contract ID which is the predecessor
+        // The predecessor is the FT contract reporting the transfer. Trusting it rather than the
+        // signer lets DAOs, multisigs and relayers deposit on behalf of `sender_id`.
         let ft_contract_id = env::predecessor_account_id();
-        if let Some(mint_currency) = self.mint_currency.clone() {
-            // Ensure only the specified FT can be used
-            require!(
-                ft_contract_id == mint_currency,
-                "FT contract ID does not match"
-            );
-
-            //get the signer which is the person who initiated the transaction
-            let signer_id = env::signer_account_id();
-
-            //make sure that the signer isn't the predecessor. This is so that we're sure
-            //this was called via a cross-contract call
-            assert_ne!(
-                ft_contract_id,
-                signer_id,
-                "ft_on_transfer should only be called via cross-contract call"
-            );
-            //make sure the owner ID is the signer. 
-            assert_eq!(
-                sender_id,
-                signer_id,
-                "owner_id should be signer_id"
-            );
-
-            // Add the amount to the user's current balance
-            let mut cur_bal = self.ft_deposits.get(&signer_id).unwrap_or(0);
-            cur_bal += amount.0;
-            self.ft_deposits.insert(&signer_id, &cur_bal);
+        if self.mint_currency.as_ref() != Some(&ft_contract_id) {
+            // Not the token this collection is priced in, refund the whole transfer
+            log!("{} is not the mint currency, refunding {}", ft_contract_id, amount.0);
+            return amount;
+        }
 
+        // Add the amount to the sender's current balance
+        self.internal_credit_ft(&sender_id, amount.0);
+        Event::FtDeposit { account_id: &sender_id, amount: &amount }.emit();
+
+        if !msg.is_empty() {
+            let FtTransferMsg::Mint { token_owner_id, quantity, allowlist_proof } =
+                near_sdk::serde_json::from_str(&msg)
+                    .unwrap_or_else(|_| env::panic_str("Invalid ft_transfer_call msg"));
+            let token_owner_id = token_owner_id.unwrap_or_else(|| sender_id.clone());
+            return self.internal_ft_mint(&sender_id, amount.0, token_owner_id, quantity, allowlist_proof);
         }
 
         U128(0)
     }
-}
\ No newline at end of file
+}
+
+impl Contract {
+    pub(crate) fn internal_credit_ft(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.ft_deposits.get(account_id).unwrap_or(0);
+        self.ft_deposits.insert(account_id, &balance.checked_add(amount).unwrap());
+    }
+
+    pub(crate) fn internal_debit_ft(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.ft_deposits.get(account_id).unwrap_or(0);
+        require!(balance >= amount, "Not enough FT deposited");
+        self.ft_deposits.insert(account_id, &(balance - amount));
+    }
+
+    /// Mint paid by an `ft_transfer_call` of `amount`, which has to cover the price on its own.
+    /// Returns the part of `amount` the mint didn't spend, which the FT contract refunds to
+    /// `minter`; FT deposited earlier stays in escrow.
+    fn internal_ft_mint(
+        &mut self,
+        minter: &AccountId,
+        amount: u128,
+        token_owner_id: AccountId,
+        quantity: u64,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> U128 {
+        require!(self.sequential_ids, "Minting through ft_transfer_call needs contract-assigned ids");
+        let specs = (0..quantity)
+            .map(|_| TokenSpec {
+                token_id: None,
+                token_owner_id: token_owner_id.clone(),
+                token_metadata: None,
+            })
+            .collect();
+
+        // No NEAR comes with ft_on_transfer, so vaults and storage are paid from `near_deposit`
+        let near_balance = self.near_deposits.get(minter).unwrap_or(0);
+        let escrowed = self.ft_deposits.get(minter).unwrap_or(0);
+        let (_, near_used) = self.internal_mint(minter, specs, allowlist_proof.as_ref(), near_balance);
+        self.near_deposits.insert(minter, &(near_balance - near_used));
+
+        let total_price = escrowed - self.ft_deposits.get(minter).unwrap_or(0);
+        require!(amount >= total_price, "Transferred amount does not cover the mint price");
+        let unused = amount - total_price;
+        if unused > 0 {
+            self.internal_debit_ft(minter, unused);
+            Event::FtWithdraw { account_id: minter, amount: &U128(unused) }.emit();
+        }
+        U128(unused)
+    }
+
+    /// Chain `payment` to the mint currency after a `storage_deposit` for `account_id`, unless a
+    /// registration of theirs already succeeded. The account is only recorded as registered
+    /// by `resolve_ft_registration`, so a failed registration is retried on the next mint.
+    pub(crate) fn internal_after_ft_registration(
+        &self,
+        ft_id: &AccountId,
+        account_id: &AccountId,
+        payment: Promise,
+    ) -> Promise {
+        if self.ft_registered.contains(account_id) {
+            return payment;
+        }
+        Promise::new(ft_id.clone())
+            .function_call(
+                "storage_deposit".to_string(),
+                json!({
+                    "account_id": account_id.to_string()
+                }).to_string().into_bytes().to_vec(),
+                NearToken::from_yoctonear(FT_STORAGE_DEPOSIT),
+                GAS_FOR_FT_STORAGE_DEPOSIT,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_FT_REGISTRATION)
+                    .resolve_ft_registration(account_id.clone()),
+            )
+            .then(payment)
+    }
+
+    /// Callback to chain after a transfer paid from the FT deposit of `account_id`, crediting
+    /// back whatever the transfer didn't use.
+    pub(crate) fn ft_payment_callback(account_id: &AccountId, amount: u128) -> Promise {
+        Self::ext(env::current_account_id())
+            .with_static_gas(GAS_FOR_RESOLVE_FT_PAYMENT)
+            .resolve_ft_payment(account_id.clone(), U128(amount))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Withdraw unused FT deposited to pay for mints. Requires 1 yoctoNEAR; if the transfer
+    /// fails the amount is credited back.
+    #[payable]
+    pub fn withdraw_ft_deposit(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let ft_id = self
+            .mint_currency
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Collection is not priced in FT"));
+        let account_id = env::predecessor_account_id();
+        require!(amount.0 > 0, "Amount has to be positive");
+        self.internal_debit_ft(&account_id, amount.0);
+
+        Event::FtWithdraw { account_id: &account_id, amount: &amount }.emit();
+
+        Promise::new(ft_id)
+            .function_call(
+                "ft_transfer".to_string(),
+                json!({
+                    "receiver_id": account_id.to_string(),
+                    "amount": amount,
+                }).to_string().into_bytes().to_vec(),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_FT_TRANSFER,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_FT_PAYMENT)
+                    .resolve_withdraw_ft_deposit(account_id, amount),
+            )
+    }
+
+    /// Deposit the attached NEAR to pay the storage and vaults of mints made through
+    /// `ft_transfer_call` by `account_id` (the caller when `None`). A new depositor's entry
+    /// is paid out of the deposit.
+    #[payable]
+    pub fn near_deposit(&mut self, account_id: Option<AccountId>) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let deposit = env::attached_deposit().as_yoctonear();
+        let initial_storage = env::storage_usage();
+        let balance = self.near_deposits.get(&account_id).unwrap_or(0);
+        self.near_deposits.insert(&account_id, &(balance + deposit));
+
+        let storage_cost = NEAR_PER_STORAGE * env::storage_usage().saturating_sub(initial_storage) as u128;
+        require!(
+            deposit > storage_cost,
+            format!("Attached deposit must be more than {} yoctoNEAR", storage_cost)
+        );
+        let amount = deposit - storage_cost;
+        self.near_deposits.insert(&account_id, &(balance + amount));
+        Event::NearDeposit { account_id: &account_id, amount: &U128(amount) }.emit();
+    }
+
+    /// NEAR `account_id` deposited for mints through `ft_transfer_call` and hasn't used.
+    pub fn near_deposits_of(&self, account_id: AccountId) -> U128 {
+        U128(self.near_deposits.get(&account_id).unwrap_or(0))
+    }
+
+    /// Withdraw NEAR deposited with `near_deposit` that mints didn't use. Requires 1 yoctoNEAR.
+    #[payable]
+    pub fn withdraw_near_deposit(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(amount.0 > 0, "Amount has to be positive");
+        let balance = self.near_deposits.get(&account_id).unwrap_or(0);
+        require!(balance >= amount.0, "Not enough NEAR deposited");
+        self.near_deposits.insert(&account_id, &(balance - amount.0));
+
+        Event::NearWithdraw { account_id: &account_id, amount: &amount }.emit();
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(amount.0))
+    }
+
+    /// Callback of the `storage_deposit` a mint forwards for `account_id`, recording the account
+    /// as registered with the mint currency once it succeeded.
+    #[private]
+    pub fn resolve_ft_registration(&mut self, account_id: AccountId) -> bool {
+        let registered = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        if registered {
+            self.ft_registered.insert(&account_id);
+        }
+        registered
+    }
+
+    /// Callback of `withdraw_ft_deposit`, restoring the deposit if the transfer failed.
+    #[private]
+    pub fn resolve_withdraw_ft_deposit(&mut self, account_id: AccountId, amount: U128) -> bool {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        if !succeeded {
+            self.internal_credit_ft(&account_id, amount.0);
+            Event::FtWithdrawFailed { account_id: &account_id, amount: &amount }.emit();
+        }
+        succeeded
+    }
+
+    /// Callback of the FT transfers paying for a mint. `ft_transfer` uses the whole amount when
+    /// it succeeds and `ft_transfer_call` returns how much it used; the rest goes back to the
+    /// FT deposit of the minter.
+    #[private]
+    pub fn resolve_ft_payment(&mut self, account_id: AccountId, amount: U128) -> U128 {
+        let used = match env::promise_result(0) {
+            PromiseResult::Successful(value) if value.is_empty() => amount.0,
+            PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<U128>(&value)
+                .map(|used| used.0.min(amount.0))
+                .unwrap_or(amount.0),
+            PromiseResult::Failed => 0,
+        };
+
+        let refund = amount.0 - used;
+        if refund > 0 {
+            self.internal_credit_ft(&account_id, refund);
+            Event::FtMintRefund { account_id: &account_id, amount: &U128(refund) }.emit();
+        }
+        U128(used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{context, setup, transfers, MINT_PRICE};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    fn token_spec() -> TokenSpec {
+        TokenSpec { token_id: None, token_owner_id: accounts(1), token_metadata: None }
+    }
+
+    /// An FT-priced collection with sequential ids where `accounts(1)` has 500 in escrow and
+    /// enough NEAR deposited for a mint of `quantity` tokens.
+    fn setup_ft_mint(quantity: u64) -> Contract {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.sequential_ids = true;
+        contract.internal_credit_ft(&accounts(1), 500);
+        let quote = contract.quote_mint(accounts(1), U64(quantity)).0;
+        contract.near_deposits.insert(&accounts(1), &quote);
+        testing_env!(context("usdc.near".parse().unwrap(), 0).build());
+        contract
+    }
+
+    fn mint_msg(quantity: u64) -> String {
+        format!("{{\"action\":\"mint\",\"quantity\":{}}}", quantity)
+    }
+
+    fn resolve_ft_payment(contract: &mut Contract, result: PromiseResult, amount: u128) -> u128 {
+        testing_env!(
+            context("art.launchpad.near".parse().unwrap(), 0).build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        contract.resolve_ft_payment(accounts(1), U128(amount)).0
+    }
+
+    #[test]
+    fn debits_and_credits_the_escrow() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.internal_credit_ft(&accounts(1), 100);
+        contract.internal_debit_ft(&accounts(1), 30);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 70);
+        assert_eq!(contract.ft_deposits_of(accounts(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough FT deposited")]
+    fn debits_at_most_the_escrow() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.internal_credit_ft(&accounts(1), 100);
+        contract.internal_debit_ft(&accounts(1), 101);
+    }
+
+    #[test]
+    fn mint_debits_the_price_from_the_escrow() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.sequential_ids = true;
+        contract.internal_credit_ft(&accounts(1), 3 * MINT_PRICE);
+        let quote = contract.quote_mint(accounts(1), U64(2)).0;
+
+        testing_env!(context(accounts(1), quote).build());
+        contract.nft_mint_batch(vec![token_spec(), token_spec()], None);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), MINT_PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient price to mint")]
+    fn mint_needs_the_price_in_escrow() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.sequential_ids = true;
+        contract.internal_credit_ft(&accounts(1), MINT_PRICE - 1);
+        let quote = contract.quote_mint(accounts(1), U64(1)).0;
+
+        testing_env!(context(accounts(1), quote).build());
+        contract.nft_mint(None, accounts(1), None, None);
+    }
+
+    #[test]
+    fn keeps_a_successful_ft_transfer() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        assert_eq!(resolve_ft_payment(&mut contract, PromiseResult::Successful(vec![]), 100), 100);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 0);
+    }
+
+    #[test]
+    fn refunds_what_ft_transfer_call_did_not_use() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.internal_credit_ft(&accounts(1), 5);
+        let result = PromiseResult::Successful(b"\"30\"".to_vec());
+        assert_eq!(resolve_ft_payment(&mut contract, result, 100), 30);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 75);
+    }
+
+    #[test]
+    fn uses_at_most_the_transferred_amount() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        let result = PromiseResult::Successful(b"\"300\"".to_vec());
+        assert_eq!(resolve_ft_payment(&mut contract, result, 100), 100);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 0);
+    }
+
+    #[test]
+    fn refunds_a_failed_transfer() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        assert_eq!(resolve_ft_payment(&mut contract, PromiseResult::Failed, 100), 0);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 100);
+    }
+    #[test]
+    fn ft_mint_refunds_what_the_price_did_not_use() {
+        let mut contract = setup_ft_mint(2);
+        let unused = contract.ft_on_transfer(accounts(1), U128(3 * MINT_PRICE), mint_msg(2));
+        assert_eq!(unused.0, MINT_PRICE);
+        // The unused part goes back with the transfer, the earlier escrow stays
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 500);
+        assert_eq!(contract.near_deposits_of(accounts(1)).0, 0);
+        assert_eq!(contract.index, 2);
+    }
+
+    #[test]
+    fn ft_mint_with_the_exact_price_refunds_nothing() {
+        let mut contract = setup_ft_mint(1);
+        assert_eq!(contract.ft_on_transfer(accounts(1), U128(MINT_PRICE), mint_msg(1)).0, 0);
+        assert_eq!(contract.ft_deposits_of(accounts(1)), 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transferred amount does not cover the mint price")]
+    fn ft_mint_does_not_dip_into_the_escrow() {
+        let mut contract = setup_ft_mint(1);
+        contract.ft_on_transfer(accounts(1), U128(MINT_PRICE - 1), mint_msg(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be at least")]
+    fn ft_mint_needs_a_near_deposit() {
+        let mut contract = setup_ft_mint(1);
+        contract.near_deposits.remove(&accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(MINT_PRICE), mint_msg(1));
+    }
+
+    #[test]
+    fn ft_of_another_token_is_refunded() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        testing_env!(context("usdt.near".parse().unwrap(), 0).build());
+        assert_eq!(contract.ft_on_transfer(accounts(1), U128(100), String::new()).0, 100);
+        assert!(contract.ft_deposits.get(&accounts(1)).is_none());
+    }
+
+    #[test]
+    fn ft_is_refunded_without_a_mint_currency() {
+        let mut contract = setup(None, false);
+        testing_env!(context("usdc.near".parse().unwrap(), 0).build());
+        assert_eq!(contract.ft_on_transfer(accounts(1), U128(100), String::new()).0, 100);
+        assert!(contract.ft_deposits.get(&accounts(1)).is_none());
+    }
+
+    #[test]
+    fn ft_relayed_for_the_sender_is_credited_to_the_sender() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        testing_env!(context("usdc.near".parse().unwrap(), 0).signer_account_id(accounts(2)).build());
+        assert_eq!(contract.ft_on_transfer(accounts(1), U128(100), String::new()).0, 0);
+        assert_eq!(contract.ft_deposits.get(&accounts(1)), Some(100));
+        assert!(contract.ft_deposits.get(&accounts(2)).is_none());
+    }
+
+    #[test]
+    fn near_deposits_can_be_withdrawn() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        testing_env!(context(accounts(1), 10u128.pow(24)).build());
+        contract.near_deposit(None);
+        let balance = contract.near_deposits_of(accounts(1)).0;
+        // The new entry's storage is kept out of the deposit
+        assert!(balance > 0 && balance < 10u128.pow(24));
+
+        testing_env!(context(accounts(1), 1).build());
+        contract.withdraw_near_deposit(U128(balance));
+        assert_eq!(contract.near_deposits_of(accounts(1)).0, 0);
+        assert_eq!(transfers(), vec![(accounts(1), balance)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough NEAR deposited")]
+    fn near_deposits_can_not_be_overdrawn() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        testing_env!(context(accounts(1), 10u128.pow(24)).build());
+        contract.near_deposit(None);
+        let balance = contract.near_deposits_of(accounts(1)).0;
+
+        testing_env!(context(accounts(1), 1).build());
+        contract.withdraw_near_deposit(U128(balance + 1));
+    }
+}
@@ -28,15 +28,28 @@ use near_contract_standards::non_fungible_token::events::NftMint;
 use near_contract_standards::non_fungible_token::{Token, TokenId};
 use near_contract_standards::fungible_token::Balance;
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
-use near_sdk::json_types::U128;
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedSet};
+use near_sdk::json_types::{Base58CryptoHash, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, require, AccountId, BorshStorageKey, PanicOnDefault, Promise, PromiseOrValue, NearToken, Gas, 
-    serde_json::json,
+    assert_one_yocto, env, near_bindgen, require, AccountId, BorshStorageKey, CryptoHash, PromiseResult, PanicOnDefault, Promise, PromiseOrValue, NearToken, Gas, 
+    serde_json::json, log,
 };
 use std::collections::HashMap;
 
+pub use crate::events::Event;
+pub use crate::phases::{AllowlistProof, MintPhase};
+pub use crate::reveal::Reveal;
+
+mod closure;
+mod events;
 mod ft_balances;
+mod limits;
+mod phases;
+mod random;
+mod reveal;
+mod sequential;
+mod upgrade;
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
@@ -68,12 +81,107 @@ pub struct Contract {
     pub balances_by_owner: LookupMap<AccountId, Balance>,
 
     pub holders: UnorderedSet<AccountId>,
+
+    //the launchpad that deployed this collection and may push code upgrades to it
+    pub factory_id: AccountId,
+
+    //hash of the code the owner agreed to upgrade to
+    pub authorized_upgrade: Option<Base58CryptoHash>,
+
+    //basis points of each mint price paid to the launchpad
+    pub platform_fee_bps: u128,
+
+    //nanosecond timestamps bounding when minting is open
+    pub sale_start: Option<u64>,
+    pub sale_end: Option<u64>,
+
+    //whether every token gets a vault holding its share of the mint price
+    pub vaults_enabled: bool,
+
+    //whether tokens are bound to the account they were minted to
+    pub soulbound: bool,
+
+    //rewards credited to holders by burns and not withdrawn yet
+    pub unclaimed_balance: u128,
+
+    //set by `close_collection`, after which minting is stopped for good
+    pub closed_at: Option<u64>,
+    pub redeem_deadline: Option<u64>,
+    pub sweep_account_id: Option<AccountId>,
+
+    //whether the unclaimed rewards were swept after the redeem deadline
+    pub swept: bool,
+
+    //sale schedule ordered by start time, replaces `mint_price` and the sale window when set
+    pub phases: Vec<MintPhase>,
+
+    //(phase name, account) pairs allowed to mint during allowlist-only phases
+    pub allowlist: LookupSet<(String, AccountId)>,
+
+    //how many tokens each account minted during each phase
+    pub phase_mints: LookupMap<(String, AccountId), u64>,
+
+    //how many tokens one account can mint in total and per transaction
+    pub max_per_wallet: Option<u64>,
+    pub max_per_tx: Option<u64>,
+
+    //how many tokens each account minted, across every phase and currency
+    pub minted_by_account: LookupMap<AccountId, u64>,
+
+    //whether the contract assigns token ids from `index` and builds their metadata
+    pub sequential_ids: bool,
+    pub title_pattern: Option<String>,
+
+    //whether contract-assigned ids are drawn at random from the ids not minted yet
+    pub random_ids: bool,
+    //positions of the random id pool holding another id than `position + 1`
+    pub id_swaps: LookupMap<u64, u64>,
+
+    //hash of the final artwork committed to before minting, hides metadata until the reveal
+    pub provenance_hash: Option<String>,
+    pub placeholder_uri: Option<String>,
+    pub reveal: Option<Reveal>,
+
+    //accounts the mint currency confirmed a storage_deposit of this contract for
+    pub ft_registered: LookupSet<AccountId>,
+
+    //NEAR deposited to pay the storage and vaults of mints made through ft_transfer_call
+    pub near_deposits: LookupMap<AccountId, u128>,
 }
 
 const NEAR_PER_STORAGE: u128 = 10_000_000_000_000_000_000;
 //the minimum storage to have a sale on the contract.
 const STORAGE_PER_SALE: u128 = 1000 * NEAR_PER_STORAGE;
 const VAULT_STORAGE: u128 = 20_000_000_000_000_000_000_000;
+const VAULT_CODE: &[u8] = include_bytes!("./vault/vault.wasm");
+//storage charged for each minted token: the token, its enumeration entries, its metadata
+//and the minter's counters
+const STORAGE_PER_TOKEN: u128 = 1000 * NEAR_PER_STORAGE;
+//attached to each `storage_deposit` a mint forwards to the mint currency contract
+const FT_STORAGE_DEPOSIT: u128 = 30_000_000_000_000_000_000_000;
+//gas attached to the promises of a mint
+const GAS_FOR_VAULT_INIT: Gas = Gas::from_tgas(20);
+const GAS_FOR_VAULT_DEPOSIT_NEAR: Gas = Gas::from_tgas(20);
+const GAS_FOR_FT_STORAGE_DEPOSIT: Gas = Gas::from_tgas(20);
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_tgas(50);
+const GAS_FOR_OWNER_FT_TRANSFER: Gas = Gas::from_tgas(50);
+const GAS_FOR_DEPOSIT_PLATFORM_FEE: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE_FT_PAYMENT: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE_FT_REGISTRATION: Gas = Gas::from_tgas(5);
+//minting one token on this contract and scheduling its promises
+const GAS_PER_MINTED_TOKEN: Gas = Gas::from_tgas(5);
+
+/// A token to mint with `nft_mint_batch`. Id and metadata are ignored when the contract
+/// assigns sequential ids, and required otherwise.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenSpec {
+    #[serde(default)]
+    pub token_id: Option<TokenId>,
+    pub token_owner_id: AccountId,
+    #[serde(default)]
+    pub token_metadata: Option<TokenMetadata>,
+}
 
 #[derive(BorshSerialize, BorshStorageKey)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -87,11 +195,18 @@ enum StorageKey {
     FTDeposits,
     BalancesByOwner,
     Holders,
+    Allowlist,
+    PhaseMints,
+    MintedByAccount,
+    IdSwaps,
+    FtRegistered,
+    NearDeposits,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         owner_id: AccountId, 
         metadata: NFTContractMetadata,
@@ -100,9 +215,33 @@ impl Contract {
         payment_split_percent: U128,
         total_supply: U128,
         burn_fee: U128,
+        platform_fee_bps: Option<U128>,
+        sale_start: Option<U64>,
+        sale_end: Option<U64>,
+        vaults_enabled: Option<bool>,
+        soulbound: Option<bool>,
+        max_per_wallet: Option<U64>,
+        max_per_tx: Option<U64>,
+        provenance_hash: Option<String>,
+        placeholder_uri: Option<String>,
     ) -> Self {
         require!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
+        let platform_fee_bps = platform_fee_bps.map(|bps| bps.0).unwrap_or(0);
+        require!(
+            payment_split_percent.0 * 100 + platform_fee_bps <= 10_000,
+            "Payment split and platform fee exceed the mint price"
+        );
+        if let (Some(start), Some(end)) = (sale_start, sale_end) {
+            require!(start.0 < end.0, "Sale start has to be before sale end");
+        }
+        let vaults_enabled = vaults_enabled.unwrap_or(true);
+        if !vaults_enabled {
+            require!(
+                payment_split_percent.0 == 0,
+                "Payment split requires vaults to be enabled"
+            );
+        }
         Self {
             tokens: NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
@@ -122,10 +261,38 @@ impl Contract {
             burn_fee: burn_fee.0,
             balances_by_owner: LookupMap::new(StorageKey::BalancesByOwner),
             holders: UnorderedSet::new(StorageKey::Holders),
+            factory_id: env::predecessor_account_id(),
+            authorized_upgrade: None,
+            platform_fee_bps,
+            sale_start: sale_start.map(|start| start.0),
+            sale_end: sale_end.map(|end| end.0),
+            vaults_enabled,
+            soulbound: soulbound.unwrap_or(false),
+            unclaimed_balance: 0,
+            closed_at: None,
+            redeem_deadline: None,
+            sweep_account_id: None,
+            swept: false,
+            phases: Vec::new(),
+            allowlist: LookupSet::new(StorageKey::Allowlist),
+            phase_mints: LookupMap::new(StorageKey::PhaseMints),
+            max_per_wallet: max_per_wallet.map(|max| max.0),
+            max_per_tx: max_per_tx.map(|max| max.0),
+            minted_by_account: LookupMap::new(StorageKey::MintedByAccount),
+            sequential_ids: false,
+            title_pattern: None,
+            random_ids: false,
+            id_swaps: LookupMap::new(StorageKey::IdSwaps),
+            provenance_hash,
+            placeholder_uri,
+            reveal: None,
+            ft_registered: LookupSet::new(StorageKey::FtRegistered),
+            near_deposits: LookupMap::new(StorageKey::NearDeposits),
         }
     }
 
-    /// Mint a new token with ID=`token_id` belonging to `token_owner_id`.
+    /// Mint a new token with ID=`token_id` belonging to `token_owner_id`. The attached deposit
+    /// has to cover `quote_mint`; the excess is refunded.
     ///
     /// Since this example implements metadata, it also requires per-token metadata to be provided
     /// in this call. `self.tokens.mint` will also require it to be Some, since
@@ -133,117 +300,48 @@ impl Contract {
     ///
     /// `self.tokens.mint` will enforce `predecessor_account_id` to equal the `owner_id` given in
     /// initialization call to `new`.
+    ///
+    /// When the collection assigns sequential ids (see `set_mint_mode`), `token_id` and
+    /// `token_metadata` are ignored and can be left out.
+    ///
+    /// `allowlist_proof` is only needed to mint in an allowlist-only phase with a merkle root
+    /// when the caller isn't on the phase's stored allowlist.
     #[payable]
     pub fn nft_mint(
         &mut self,
-        token_id: TokenId,
+        token_id: Option<TokenId>,
         token_owner_id: AccountId,
-        token_metadata: TokenMetadata,
+        token_metadata: Option<TokenMetadata>,
+        allowlist_proof: Option<AllowlistProof>,
     ) -> Token {
-        let collection_owner = &self.tokens.owner_id;
-        let owner = env::predecessor_account_id(); 
-        self.holders.insert(&owner);
-        // assert_eq!(owner, self.tokens.owner_id, "Unauthorized");
-
-        let code = include_bytes!("./vault/vault.wasm").to_vec();
-        let contract_bytes = code.len() as u128;
-        let minimum_needed = NEAR_PER_STORAGE * contract_bytes + VAULT_STORAGE;
-
-        let deposit: u128 = env::attached_deposit().as_yoctonear();
-        if let Some(_) = self.mint_currency.clone() {
-            let amount = self.ft_deposits_of(owner.clone());
-            require!(deposit >= minimum_needed && amount >= self.mint_price, "Insufficient price to mint");
-        } else {
-            require!(deposit >= self.mint_price + minimum_needed, "Insufficient price to mint");
-        }
-
-        let current_id = env::current_account_id();
-
-        let vault_amount = self.mint_price.checked_mul(self.payment_split_percent)
-            .unwrap().checked_div(100u128).unwrap();
-
-        let owner_amount = self.mint_price.checked_sub(vault_amount).unwrap();
-
-        // Deploy the vault contract
-        let vault_account_id: AccountId = format!("{}.{}", token_id, current_id).parse().unwrap();
-        Promise::new(vault_account_id.clone())
-            .create_account()
-            .deploy_contract(code)
-            .transfer(NearToken::from_yoctonear(minimum_needed))
-            .function_call(
-                // Init the vault contract
-                "init".to_string(),
-                if let Some(ft_id) = self.mint_currency.clone() {
-                    json!({
-                        "ft_contract": ft_id.to_string()
-                    })
-                } else {
-                    json!({})
-                }.to_string().into_bytes().to_vec(),
-                NearToken::from_millinear(0),
-                Gas::from_tgas(20)
-            )
-            .then(
-                // Deposit ft or near
-                if let Some(ft_id) = self.mint_currency.clone() {
-                    Promise::new(ft_id.clone()).function_call(
-                        "storage_deposit".to_string(), 
-                        json!({
-                            "account_id": vault_account_id.to_string()
-                        }).to_string().into_bytes().to_vec(),
-                        NearToken::from_millinear(30),
-                        Gas::from_tgas(20),
-                    );
-
-                    Promise::new(ft_id.clone()).function_call(
-                        "ft_transfer_call".to_string(), 
-                        json!({
-                            "receiver_id": vault_account_id.to_string(),
-                            "amount": vault_amount.to_string(),
-                            "msg": "",
-                        }).to_string().into_bytes().to_vec(),
-                        NearToken::from_yoctonear(1),
-                        Gas::from_tgas(50),
-                    );
-
-                    Promise::new(ft_id.clone()).function_call(
-                        "storage_deposit".to_string(), 
-                        json!({
-                            "account_id": collection_owner.clone().to_string()
-                        }).to_string().into_bytes().to_vec(),
-                        NearToken::from_millinear(30),
-                        Gas::from_tgas(20),
-                    );
-
-                    Promise::new(ft_id.clone()).function_call(
-                        "ft_transfer".to_string(), 
-                        json!({
-                            "receiver_id": collection_owner.clone().to_string(),
-                            "amount": owner_amount.to_string(),
-                            "msg": "",
-                        }).to_string().into_bytes().to_vec(),
-                        NearToken::from_yoctonear(1),
-                        Gas::from_tgas(50),
-                    )
-                } else {
-                    Promise::new(collection_owner.clone()).transfer(NearToken::from_yoctonear(owner_amount));
-                    Promise::new(vault_account_id.clone()).function_call(
-                        "deposit_near".to_string(),
-                        json!({}).to_string().into_bytes().to_vec(),
-                        NearToken::from_yoctonear(vault_amount),
-                        Gas::from_tgas(20),
-                    )
-                }
-            );
-
-        self.index = self.index.checked_add(1).unwrap();
-        if self.total_supply > 0 {
-            require!(self.total_supply >= self.index, "Exceeded total supply");
-        }
+        let spec = TokenSpec { token_id, token_owner_id, token_metadata };
+        let (mut tokens, deposit_used) = self.internal_mint(
+            &env::predecessor_account_id(),
+            vec![spec],
+            allowlist_proof.as_ref(),
+            env::attached_deposit().as_yoctonear(),
+        );
+        refund_unused_deposit(deposit_used);
+        tokens.pop().unwrap()
+    }
 
-        let token = self.tokens.internal_mint_with_refund(token_id, token_owner_id, Some(token_metadata), None);
-        NftMint { owner_id: &token.owner_id, token_ids: &[&token.token_id], memo: None }.emit();
-        token
+    /// Mint several tokens in one call. The attached deposit has to cover `quote_mint` for the
+    /// whole batch; the excess is refunded. How many tokens fit in a batch is bounded by the
+    /// prepaid gas, see `quote_mint_gas`.
+    #[payable]
+    pub fn nft_mint_batch(
+        &mut self,
+        tokens: Vec<TokenSpec>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Vec<Token> {
+        let (tokens, deposit_used) = self.internal_mint(
+            &env::predecessor_account_id(),
+            tokens,
+            allowlist_proof.as_ref(),
+            env::attached_deposit().as_yoctonear(),
+        );
+        refund_unused_deposit(deposit_used);
+        tokens
     }
 
     //Allows users to deposit storage. This is to cover the cost of storing sale objects on the contract
@@ -278,6 +376,7 @@ impl Contract {
     // Burn an NFT by its token ID
     #[payable]
     pub fn burn(&mut self, token_id: TokenId) {
+        self.assert_redeemable();
         let owner = env::predecessor_account_id();
 
         // Ensure the owner has the NFT
@@ -343,25 +442,29 @@ impl Contract {
                 let mut balance = self.balances_by_owner.get(&other).unwrap_or(0);
                 balance = balance.checked_add(amount_to_holder).unwrap();
                 self.balances_by_owner.insert(&other, &balance);
+                self.unclaimed_balance = self.unclaimed_balance.checked_add(amount_to_holder).unwrap();
             }
         }
 
-        let current_id = env::current_account_id();
-        let vault_account_id: AccountId = format!("{}.{}", token_id, current_id).parse().unwrap();
-
-        Promise::new(vault_account_id.clone()).function_call(
-            "withdraw".to_string(),
-            json!({
-                "owner": owner.to_string(),
-                "burn_fee": self.burn_fee.to_string(),
-            }).to_string().into_bytes().to_vec(),
-            NearToken::from_yoctonear(1),
-            Gas::from_tgas(100)
-        );
+        if self.vaults_enabled {
+            let current_id = env::current_account_id();
+            let vault_account_id: AccountId = format!("{}.{}", token_id, current_id).parse().unwrap();
+
+            Promise::new(vault_account_id.clone()).function_call(
+                "withdraw".to_string(),
+                json!({
+                    "owner": owner.to_string(),
+                    "burn_fee": self.burn_fee.to_string(),
+                }).to_string().into_bytes().to_vec(),
+                NearToken::from_yoctonear(1),
+                Gas::from_tgas(100)
+            );
+        }
     }
 
     #[payable]
     pub fn withdraw(&mut self) {
+        self.assert_redeemable();
         let owner = env::predecessor_account_id();
         let balance: u128 = self.balances_by_owner.get(&owner).unwrap_or(0);
 
@@ -392,11 +495,14 @@ impl Contract {
             }
 
             self.balances_by_owner.insert(&owner, &0u128).unwrap();
+            self.unclaimed_balance = self.unclaimed_balance.saturating_sub(balance);
         }
     }
 
     #[payable]
     pub fn withdraw_from_vault(&mut self, token_id: TokenId, percent: U128) {
+        require!(self.vaults_enabled, "This collection has no vaults");
+        self.assert_redeemable();
         let owner = env::predecessor_account_id();
         let amount: u128 = self.mint_price
             .checked_mul(percent.0).unwrap()
@@ -440,6 +546,334 @@ impl Contract {
     pub fn balance_of(&self, owner: AccountId) -> u128 {
         self.balances_by_owner.get(&owner).unwrap_or(0)
     }
+
+    /// NEAR that minting `count` tokens now takes from the attached deposit of `account_id`;
+    /// anything attached above it is refunded. FT-priced collections take the price from the
+    /// FT deposit instead. Custom metadata larger than the flat per-token storage costs extra.
+    pub fn quote_mint(&self, account_id: AccountId, count: U64) -> U128 {
+        if let Some(left) = self.mints_left(account_id) {
+            require!(count.0 <= left.0, "Not enough mints left");
+        }
+        U128(self.internal_mint_deposit(self.internal_current_price(), count.0))
+    }
+
+    /// Gas the promises of minting `count` tokens now attach, which the mint call needs left
+    /// on top of its own execution. Batches needing more than a transaction can carry have to
+    /// be split.
+    pub fn quote_mint_gas(&self, count: U64) -> Gas {
+        self.internal_mint_gas(self.internal_current_price(), count.0)
+    }
+
+    /// Start and end (nanosecond timestamps) of the window in which minting is open.
+    pub fn sale_window(&self) -> (Option<U64>, Option<U64>) {
+        (self.sale_start.map(U64), self.sale_end.map(U64))
+    }
+}
+
+impl Contract {
+    /// Price of a token in the current phase, or the collection's price without phases.
+    fn internal_current_price(&self) -> u128 {
+        if self.phases.is_empty() {
+            self.mint_price
+        } else {
+            self.internal_current_phase()
+                .unwrap_or_else(|| env::panic_str("No mint phase is active"))
+                .price
+                .0
+        }
+    }
+
+    /// Gas attached to the promises of a mint of `quantity` tokens at `price`: a vault
+    /// deployment per token when vaults are enabled, and paying the collection owner and the
+    /// launchpad.
+    fn internal_mint_gas(&self, price: u128, quantity: u64) -> Gas {
+        let pays_platform_fee = price * self.platform_fee_bps / 10_000 > 0;
+        let mut per_token = GAS_PER_MINTED_TOKEN;
+        let mut payment = Gas::from_gas(0);
+        if self.mint_currency.is_some() {
+            if self.vaults_enabled {
+                per_token = per_token
+                    .saturating_add(GAS_FOR_VAULT_INIT)
+                    .saturating_add(GAS_FOR_FT_STORAGE_DEPOSIT)
+                    .saturating_add(GAS_FOR_FT_TRANSFER_CALL)
+                    .saturating_add(GAS_FOR_RESOLVE_FT_PAYMENT);
+            }
+            if !self.ft_registered.contains(&self.tokens.owner_id) {
+                payment = payment
+                    .saturating_add(GAS_FOR_FT_STORAGE_DEPOSIT)
+                    .saturating_add(GAS_FOR_RESOLVE_FT_REGISTRATION);
+            }
+            payment = payment
+                .saturating_add(GAS_FOR_OWNER_FT_TRANSFER)
+                .saturating_add(GAS_FOR_RESOLVE_FT_PAYMENT);
+            if pays_platform_fee {
+                if !self.ft_registered.contains(&self.factory_id) {
+                    payment = payment
+                        .saturating_add(GAS_FOR_FT_STORAGE_DEPOSIT)
+                        .saturating_add(GAS_FOR_RESOLVE_FT_REGISTRATION);
+                }
+                payment = payment
+                    .saturating_add(GAS_FOR_FT_TRANSFER_CALL)
+                    .saturating_add(GAS_FOR_RESOLVE_FT_PAYMENT);
+            }
+        } else {
+            if self.vaults_enabled {
+                per_token = per_token
+                    .saturating_add(GAS_FOR_VAULT_INIT)
+                    .saturating_add(GAS_FOR_VAULT_DEPOSIT_NEAR);
+            }
+            if pays_platform_fee {
+                payment = payment.saturating_add(GAS_FOR_DEPOSIT_PLATFORM_FEE);
+            }
+        }
+        per_token.saturating_mul(quantity).saturating_add(payment)
+    }
+
+    /// NEAR a mint of `quantity` tokens at `price` takes from the attached deposit: the price
+    /// (unless paid in FT), the vault code and storage, the token storage and the
+    /// `storage_deposit`s forwarded to the FT contract for accounts not registered yet.
+    fn internal_mint_deposit(&self, price: u128, quantity: u64) -> u128 {
+        let quantity = quantity as u128;
+        let mut needed = STORAGE_PER_TOKEN * quantity;
+        if self.vaults_enabled {
+            needed += (NEAR_PER_STORAGE * VAULT_CODE.len() as u128 + VAULT_STORAGE) * quantity;
+        }
+        if self.mint_currency.is_some() {
+            // Every new vault gets registered with the FT, the collection owner and the
+            // launchpad until a registration of theirs succeeded
+            let mut registrations = 0;
+            if !self.ft_registered.contains(&self.tokens.owner_id) {
+                registrations += 1;
+            }
+            if self.vaults_enabled {
+                registrations += quantity;
+            }
+            if price * self.platform_fee_bps / 10_000 > 0 && !self.ft_registered.contains(&self.factory_id) {
+                registrations += 1;
+            }
+            needed += FT_STORAGE_DEPOSIT * registrations;
+        } else {
+            needed += price * quantity;
+        }
+        needed
+    }
+
+    /// Mint `specs` paid by `minter`, whose NEAR costs come out of `deposit`. Returns the minted
+    /// tokens and how much of `deposit` they used.
+    pub(crate) fn internal_mint(
+        &mut self,
+        minter: &AccountId,
+        specs: Vec<TokenSpec>,
+        allowlist_proof: Option<&AllowlistProof>,
+        deposit: u128,
+    ) -> (Vec<Token>, u128) {
+        self.assert_sale_open();
+        let initial_storage = env::storage_usage();
+        let specs = self.internal_resolve_specs(specs);
+        let owner = minter.clone();
+        let quantity = specs.len() as u64;
+        self.internal_use_mint_limits(&owner, quantity);
+        let price = self.internal_use_phase(&owner, quantity, allowlist_proof);
+        let collection_owner = self.tokens.owner_id.clone();
+        self.holders.insert(&owner);
+        // assert_eq!(owner, self.tokens.owner_id, "Unauthorized");
+
+        let code = VAULT_CODE.to_vec();
+        let contract_bytes = code.len() as u128;
+        let minimum_needed = NEAR_PER_STORAGE * contract_bytes + VAULT_STORAGE;
+
+        let total_price = price.checked_mul(quantity as u128).unwrap();
+
+        let quoted = self.internal_mint_deposit(price, quantity);
+        require!(
+            deposit >= quoted,
+            format!("Attached deposit must be at least {} yoctoNEAR to mint", quoted)
+        );
+        let mint_gas = self.internal_mint_gas(price, quantity);
+        require!(
+            env::prepaid_gas().saturating_sub(env::used_gas()) >= mint_gas,
+            format!("Not enough gas to mint {} tokens, their promises need {} Tgas", quantity, mint_gas.as_tgas())
+        );
+        if self.mint_currency.is_some() {
+            let amount = self.ft_deposits_of(owner.clone());
+            require!(amount >= total_price, "Insufficient price to mint");
+            self.internal_debit_ft(&owner, total_price);
+            Event::FtMintPayment { account_id: &owner, amount: &U128(total_price) }.emit();
+        }
+
+        let current_id = env::current_account_id();
+
+        let vault_amount = price.checked_mul(self.payment_split_percent)
+            .unwrap().checked_div(100u128).unwrap();
+
+        let platform_amount = price.checked_mul(self.platform_fee_bps)
+            .unwrap().checked_div(10_000u128).unwrap();
+
+        let owner_amount = price
+            .checked_sub(vault_amount).unwrap()
+            .checked_sub(platform_amount).unwrap()
+            .checked_mul(quantity as u128).unwrap();
+        let platform_amount = platform_amount.checked_mul(quantity as u128).unwrap();
+        let factory_id = self.factory_id.clone();
+
+        if self.vaults_enabled {
+            for (token_id, _, _) in specs.iter() {
+                // Deploy the vault contract
+                let vault_account_id: AccountId = format!("{}.{}", token_id, current_id).parse().unwrap();
+                let vault_promise = Promise::new(vault_account_id.clone())
+                    .create_account()
+                    .deploy_contract(code.clone())
+                    .transfer(NearToken::from_yoctonear(minimum_needed))
+                    .function_call(
+                        // Init the vault contract
+                        "init".to_string(),
+                        if let Some(ft_id) = self.mint_currency.clone() {
+                            json!({
+                                "ft_contract": ft_id.to_string()
+                            })
+                        } else {
+                            json!({})
+                        }.to_string().into_bytes().to_vec(),
+                        NearToken::from_millinear(0),
+                        GAS_FOR_VAULT_INIT
+                    )
+                    .then(
+                        // Deposit ft or near
+                        if let Some(ft_id) = self.mint_currency.clone() {
+                            Promise::new(ft_id.clone()).function_call(
+                                "storage_deposit".to_string(), 
+                                json!({
+                                    "account_id": vault_account_id.to_string()
+                                }).to_string().into_bytes().to_vec(),
+                                NearToken::from_yoctonear(FT_STORAGE_DEPOSIT),
+                                GAS_FOR_FT_STORAGE_DEPOSIT,
+                            );
+
+                            Promise::new(ft_id.clone()).function_call(
+                                "ft_transfer_call".to_string(), 
+                                json!({
+                                    "receiver_id": vault_account_id.to_string(),
+                                    "amount": vault_amount.to_string(),
+                                    "msg": "",
+                                }).to_string().into_bytes().to_vec(),
+                                NearToken::from_yoctonear(1),
+                                GAS_FOR_FT_TRANSFER_CALL,
+                            )
+                        } else {
+                            Promise::new(vault_account_id.clone()).function_call(
+                                "deposit_near".to_string(),
+                                json!({}).to_string().into_bytes().to_vec(),
+                                NearToken::from_yoctonear(vault_amount),
+                                GAS_FOR_VAULT_DEPOSIT_NEAR,
+                            )
+                        }
+                    );
+                if self.mint_currency.is_some() {
+                    vault_promise.then(Self::ft_payment_callback(&owner, vault_amount));
+                }
+            }
+        }
+
+        // Pay the collection owner and the launchpad in ft or near
+        if let Some(ft_id) = self.mint_currency.clone() {
+            if platform_amount > 0 {
+                let platform_payment = Promise::new(ft_id.clone()).function_call(
+                    "ft_transfer_call".to_string(),
+                    json!({
+                        "receiver_id": factory_id.to_string(),
+                        "amount": platform_amount.to_string(),
+                        "msg": "platform_fee",
+                    }).to_string().into_bytes().to_vec(),
+                    NearToken::from_yoctonear(1),
+                    GAS_FOR_FT_TRANSFER_CALL,
+                );
+                self.internal_after_ft_registration(&ft_id, &factory_id, platform_payment)
+                    .then(Self::ft_payment_callback(&owner, platform_amount));
+            }
+
+            let owner_payment = Promise::new(ft_id.clone()).function_call(
+                "ft_transfer".to_string(), 
+                json!({
+                    "receiver_id": collection_owner.clone().to_string(),
+                    "amount": owner_amount.to_string(),
+                    "msg": "",
+                }).to_string().into_bytes().to_vec(),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_OWNER_FT_TRANSFER,
+            );
+            self.internal_after_ft_registration(&ft_id, &collection_owner, owner_payment)
+                .then(Self::ft_payment_callback(&owner, owner_amount));
+        } else {
+            if platform_amount > 0 {
+                Promise::new(factory_id.clone()).function_call(
+                    "deposit_platform_fee".to_string(),
+                    json!({}).to_string().into_bytes().to_vec(),
+                    NearToken::from_yoctonear(platform_amount),
+                    GAS_FOR_DEPOSIT_PLATFORM_FEE,
+                );
+            }
+            Promise::new(collection_owner.clone()).transfer(NearToken::from_yoctonear(owner_amount));
+        }
+
+        self.index = self.index.checked_add(quantity as u128).unwrap();
+        if self.total_supply > 0 {
+            require!(self.total_supply >= self.index, "Exceeded total supply");
+        }
+
+        let tokens: Vec<Token> = specs
+            .into_iter()
+            .map(|(token_id, token_owner_id, token_metadata)| {
+                self.tokens.internal_mint_with_refund(token_id, token_owner_id, Some(token_metadata), None)
+            })
+            .collect();
+
+        // One event for the whole mint, with an entry per receiving account
+        let mut token_ids_by_owner: Vec<(&AccountId, Vec<&str>)> = Vec::new();
+        for token in tokens.iter() {
+            match token_ids_by_owner.iter_mut().find(|(owner_id, _)| *owner_id == &token.owner_id) {
+                Some((_, token_ids)) => token_ids.push(token.token_id.as_str()),
+                None => token_ids_by_owner.push((&token.owner_id, vec![token.token_id.as_str()])),
+            }
+        }
+        let mint_logs: Vec<NftMint> = token_ids_by_owner
+            .iter()
+            .map(|(owner_id, token_ids)| NftMint { owner_id, token_ids, memo: None })
+            .collect();
+        NftMint::emit_many(&mint_logs);
+
+        // Metadata larger than the flat per-token storage pays for its actual size
+        let storage_cost = NEAR_PER_STORAGE * env::storage_usage().saturating_sub(initial_storage) as u128;
+        let deposit_used = quoted + storage_cost.saturating_sub(STORAGE_PER_TOKEN * quantity as u128);
+        require!(
+            deposit >= deposit_used,
+            format!("Attached deposit must be at least {} yoctoNEAR to mint", deposit_used)
+        );
+
+        let tokens = tokens
+            .into_iter()
+            .map(|token| self.internal_displayed_token(token))
+            .collect();
+        (tokens, deposit_used)
+    }
+
+    fn assert_transferable(&self) {
+        require!(!self.soulbound, "Tokens of this collection are soulbound");
+    }
+
+    fn assert_sale_open(&self) {
+        require!(self.closed_at.is_none(), "Collection is closed");
+        if !self.phases.is_empty() {
+            // Phases carry their own schedule
+            return;
+        }
+        let now = env::block_timestamp();
+        if let Some(start) = self.sale_start {
+            require!(now >= start, "Sale has not started yet");
+        }
+        if let Some(end) = self.sale_end {
+            require!(now < end, "Sale has ended");
+        }
+    }
 }
 
 #[near_bindgen]
@@ -452,6 +886,7 @@ impl NonFungibleTokenCore for Contract {
         approval_id: Option<u64>,
         memo: Option<String>,
     ) {
+        self.assert_transferable();
         self.tokens.nft_transfer(receiver_id, token_id, approval_id, memo);
     }
 
@@ -464,11 +899,14 @@ impl NonFungibleTokenCore for Contract {
         memo: Option<String>,
         msg: String,
     ) -> PromiseOrValue<bool> {
+        self.assert_transferable();
         self.tokens.nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
     }
 
     fn nft_token(&self, token_id: TokenId) -> Option<Token> {
-        self.tokens.nft_token(token_id)
+        self.tokens
+            .nft_token(token_id)
+            .map(|token| self.internal_displayed_token(token))
     }
 }
 
@@ -500,6 +938,7 @@ impl NonFungibleTokenApproval for Contract {
         account_id: AccountId,
         msg: Option<String>,
     ) -> Option<Promise> {
+        self.assert_transferable();
         self.tokens.nft_approve(token_id, account_id, msg)
     }
 
@@ -530,7 +969,11 @@ impl NonFungibleTokenEnumeration for Contract {
     }
 
     fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
-        self.tokens.nft_tokens(from_index, limit)
+        self.tokens
+            .nft_tokens(from_index, limit)
+            .into_iter()
+            .map(|token| self.internal_displayed_token(token))
+            .collect()
     }
 
     fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
@@ -543,7 +986,11 @@ impl NonFungibleTokenEnumeration for Contract {
         from_index: Option<U128>,
         limit: Option<u64>,
     ) -> Vec<Token> {
-        self.tokens.nft_tokens_for_owner(account_id, from_index, limit)
+        self.tokens
+            .nft_tokens_for_owner(account_id, from_index, limit)
+            .into_iter()
+            .map(|token| self.internal_displayed_token(token))
+            .collect()
     }
 }
 
@@ -553,3 +1000,436 @@ impl NonFungibleTokenMetadataProvider for Contract {
         self.metadata.get().unwrap()
     }
 }
+
+/// Refund the part of the attached deposit above `deposit_used`.
+pub(crate) fn refund_unused_deposit(deposit_used: u128) {
+    let refund = env::attached_deposit().as_yoctonear() - deposit_used;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+    }
+}
+
+/// Refund the attached deposit minus the cost of the storage added since `initial_storage`,
+/// plus the cost of any storage freed.
+pub(crate) fn refund_storage_deposit(initial_storage: u64) {
+    let deposit = env::attached_deposit().as_yoctonear();
+    let current_storage = env::storage_usage();
+
+    let refund = if current_storage >= initial_storage {
+        let storage_cost = NEAR_PER_STORAGE * (current_storage - initial_storage) as u128;
+        require!(
+            deposit >= storage_cost,
+            format!("Attached deposit must be at least {} yoctoNEAR to cover storage", storage_cost)
+        );
+        deposit - storage_cost
+    } else {
+        deposit + NEAR_PER_STORAGE * (initial_storage - current_storage) as u128
+    };
+
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use near_contract_standards::non_fungible_token::metadata::NFT_METADATA_SPEC;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    pub(crate) const MINT_PRICE: u128 = 1_000_000;
+    const VAULT_COST: u128 = NEAR_PER_STORAGE * VAULT_CODE.len() as u128 + VAULT_STORAGE;
+
+    pub(crate) fn launchpad() -> AccountId {
+        "launchpad.near".parse().unwrap()
+    }
+
+    pub(crate) fn context(predecessor: AccountId, deposit: u128) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id("art.launchpad.near".parse().unwrap())
+            .predecessor_account_id(predecessor)
+            .attached_deposit(NearToken::from_yoctonear(deposit))
+            .prepaid_gas(Gas::from_tgas(300));
+        builder
+    }
+
+    /// A collection of 10 tokens owned by `accounts(0)`, launched by `launchpad()` with a
+    /// platform fee of 5%.
+    pub(crate) fn setup(mint_currency: Option<AccountId>, vaults_enabled: bool) -> Contract {
+        testing_env!(context(launchpad(), 0).build());
+        Contract::new(
+            accounts(0),
+            NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "Art".to_string(),
+                symbol: "ART".to_string(),
+                icon: None,
+                base_uri: None,
+                reference: None,
+                reference_hash: None,
+            },
+            U128(MINT_PRICE),
+            mint_currency,
+            U128(if vaults_enabled { 50 } else { 0 }),
+            U128(10),
+            U128(0),
+            Some(U128(500)),
+            None,
+            None,
+            Some(vaults_enabled),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Transfers the contract created, as (receiver, yoctoNEAR).
+    pub(crate) fn transfers() -> Vec<(AccountId, u128)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    MockAction::Transfer { deposit, .. } => Some((receiver_id.clone(), deposit.as_yoctonear())),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quote_mint_covers_price_vaults_and_token_storage() {
+        let contract = setup(None, true);
+        assert_eq!(
+            contract.quote_mint(accounts(1), U64(2)).0,
+            2 * (MINT_PRICE + VAULT_COST + STORAGE_PER_TOKEN)
+        );
+
+        let contract = setup(None, false);
+        assert_eq!(contract.quote_mint(accounts(1), U64(2)).0, 2 * (MINT_PRICE + STORAGE_PER_TOKEN));
+    }
+
+    #[test]
+    fn quote_mint_registers_the_owner_and_the_launchpad_once() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), true);
+        // The owner, both vaults and the launchpad get registered with the FT
+        let unregistered = 2 * (VAULT_COST + STORAGE_PER_TOKEN) + 4 * FT_STORAGE_DEPOSIT;
+        assert_eq!(contract.quote_mint(accounts(1), U64(2)).0, unregistered);
+
+        contract.ft_registered.insert(&accounts(0));
+        contract.ft_registered.insert(&launchpad());
+        assert_eq!(
+            contract.quote_mint(accounts(1), U64(2)).0,
+            unregistered - 2 * FT_STORAGE_DEPOSIT
+        );
+    }
+
+    #[test]
+    fn nft_mint_refunds_the_excess_deposit() {
+        let mut contract = setup(None, true);
+        contract.sequential_ids = true;
+        let quote = contract.quote_mint(accounts(1), U64(1)).0;
+
+        testing_env!(context(accounts(1), quote + 77).build());
+        contract.nft_mint(None, accounts(1), None, None);
+        assert!(transfers().contains(&(accounts(1), 77)));
+        assert_eq!(contract.index, 1);
+    }
+
+    #[test]
+    fn nft_mint_with_the_exact_deposit_refunds_nothing() {
+        let mut contract = setup(None, false);
+        contract.sequential_ids = true;
+        let quote = contract.quote_mint(accounts(1), U64(1)).0;
+
+        testing_env!(context(accounts(1), quote).build());
+        contract.nft_mint(None, accounts(1), None, None);
+        assert!(transfers().iter().all(|(receiver_id, _)| receiver_id != &accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be at least")]
+    fn nft_mint_requires_the_quoted_deposit() {
+        let mut contract = setup(None, true);
+        contract.sequential_ids = true;
+        let quote = contract.quote_mint(accounts(1), U64(1)).0;
+
+        testing_env!(context(accounts(1), quote - 1).build());
+        contract.nft_mint(None, accounts(1), None, None);
+    }
+
+    fn resolve_ft_registration(contract: &mut Contract, result: PromiseResult, account_id: AccountId) {
+        testing_env!(
+            context("art.launchpad.near".parse().unwrap(), 0).build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        contract.resolve_ft_registration(account_id);
+    }
+
+    #[test]
+    fn ft_mint_forwards_storage_deposits_until_registered() {
+        let mut contract = setup(Some("usdc.near".parse().unwrap()), false);
+        contract.sequential_ids = true;
+        contract.internal_credit_ft(&accounts(1), 2 * MINT_PRICE);
+        let first = contract.quote_mint(accounts(1), U64(1)).0;
+
+        testing_env!(context(accounts(1), first).build());
+        contract.nft_mint(None, accounts(1), None, None);
+        // Nothing is registered before the FT contract confirmed it
+        assert_eq!(contract.quote_mint(accounts(1), U64(1)).0, first);
+
+        resolve_ft_registration(&mut contract, PromiseResult::Failed, accounts(0));
+        assert_eq!(contract.quote_mint(accounts(1), U64(1)).0, first);
+
+        resolve_ft_registration(&mut contract, PromiseResult::Successful(vec![]), accounts(0));
+        resolve_ft_registration(&mut contract, PromiseResult::Successful(vec![]), launchpad());
+        assert_eq!(contract.quote_mint(accounts(1), U64(1)).0, first - 2 * FT_STORAGE_DEPOSIT);
+    }
+
+    fn mint_phase(name: &str, start: u64, end: Option<u64>, price: u128, max_per_wallet: Option<u64>) -> MintPhase {
+        MintPhase {
+            name: name.to_string(),
+            start: U64(start),
+            end: end.map(U64),
+            price: U128(price),
+            max_per_wallet: max_per_wallet.map(U64),
+            allowlist_only: false,
+            merkle_root: None,
+        }
+    }
+
+    /// `setup()` with a presale over [100, 200) and an open ended public sale from 200.
+    fn setup_with_phases(presale: MintPhase) -> Contract {
+        let mut contract = setup(None, false);
+        testing_env!(context(accounts(0), 0).build());
+        contract.add_phase(presale);
+        contract.add_phase(mint_phase("public", 200, None, 7, Some(2)));
+        contract
+    }
+
+    fn at(timestamp: u64) {
+        testing_env!(context(accounts(1), 0).block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn phases_apply_their_own_price() {
+        let mut contract = setup_with_phases(mint_phase("presale", 100, Some(200), 5, None));
+        at(50);
+        assert!(contract.current_phase().is_none());
+        at(150);
+        assert_eq!(contract.internal_use_phase(&accounts(1), 1, None), 5);
+        at(200);
+        assert_eq!(contract.internal_use_phase(&accounts(1), 1, None), 7);
+        assert_eq!(contract.current_phase().unwrap().name, "public");
+    }
+
+    #[test]
+    fn collections_without_phases_use_the_mint_price() {
+        let mut contract = setup(None, false);
+        assert_eq!(contract.internal_use_phase(&accounts(1), 3, None), MINT_PRICE);
+    }
+
+    #[test]
+    fn allowlisted_accounts_can_mint_in_an_allowlist_only_phase() {
+        let mut presale = mint_phase("presale", 100, Some(200), 5, None);
+        presale.allowlist_only = true;
+        let mut contract = setup_with_phases(presale);
+        contract.allowlist.insert(&("presale".to_string(), accounts(1)));
+        at(150);
+        assert_eq!(contract.internal_use_phase(&accounts(1), 1, None), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid allowlist proof")]
+    fn allowlist_only_phases_reject_other_accounts() {
+        let mut presale = mint_phase("presale", 100, Some(200), 5, None);
+        presale.allowlist_only = true;
+        let mut contract = setup_with_phases(presale);
+        contract.allowlist.insert(&("presale".to_string(), accounts(2)));
+        at(150);
+        let proof = AllowlistProof { max_quantity: U64(1), proof: vec![] };
+        contract.internal_use_phase(&accounts(1), 1, Some(&proof));
+    }
+
+    #[test]
+    fn wallet_limits_are_counted_per_phase() {
+        let mut contract = setup_with_phases(mint_phase("presale", 100, Some(200), 5, Some(2)));
+        at(150);
+        contract.internal_use_phase(&accounts(1), 2, None);
+        at(250);
+        contract.internal_use_phase(&accounts(1), 1, None);
+        contract.internal_use_phase(&accounts(1), 1, None);
+        assert_eq!(contract.phase_mints_of("presale".to_string(), accounts(1)), U64(2));
+        assert_eq!(contract.phase_mints_of("public".to_string(), accounts(1)), U64(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mint limit of phase public reached")]
+    fn wallet_limits_are_enforced() {
+        let mut contract = setup_with_phases(mint_phase("presale", 100, Some(200), 5, None));
+        at(250);
+        contract.internal_use_phase(&accounts(1), 2, None);
+        contract.internal_use_phase(&accounts(1), 1, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Phase late presale overlaps phase presale")]
+    fn overlapping_phases_are_rejected() {
+        let mut contract = setup_with_phases(mint_phase("presale", 100, Some(200), 5, None));
+        contract.add_phase(mint_phase("late presale", 150, Some(180), 5, None));
+    }
+
+    /// `setup()` with at most 3 mints per account and 2 per transaction.
+    fn setup_with_mint_limits() -> Contract {
+        let mut contract = setup(None, false);
+        testing_env!(context(accounts(0), 0).build());
+        contract.set_mint_limits(Some(U64(3)), Some(U64(2)));
+        contract
+    }
+
+    #[test]
+    fn mint_limits_count_every_mint() {
+        let mut contract = setup_with_mint_limits();
+        contract.internal_use_mint_limits(&accounts(1), 2);
+        assert_eq!(contract.mints_left(accounts(1)), Some(U64(1)));
+        contract.internal_use_mint_limits(&accounts(1), 1);
+        assert_eq!(contract.minted_by(accounts(1)), U64(3));
+        assert_eq!(contract.mints_left(accounts(1)), Some(U64(0)));
+        assert_eq!(contract.mints_left(accounts(2)), Some(U64(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 2 tokens can be minted per transaction")]
+    fn mint_limits_reject_large_transactions() {
+        let mut contract = setup_with_mint_limits();
+        contract.internal_use_mint_limits(&accounts(1), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 3 tokens can be minted per account")]
+    fn mint_limits_reject_wallets_over_the_limit() {
+        let mut contract = setup_with_mint_limits();
+        contract.internal_use_mint_limits(&accounts(1), 2);
+        contract.internal_use_mint_limits(&accounts(1), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to mint")]
+    fn mint_limits_reject_empty_mints() {
+        let mut contract = setup(None, false);
+        contract.internal_use_mint_limits(&accounts(1), 0);
+    }
+
+    #[test]
+    fn mints_left_is_bounded_by_the_remaining_supply() {
+        let mut contract = setup_with_mint_limits();
+        contract.index = 9;
+        assert_eq!(contract.mints_left(accounts(1)), Some(U64(1)));
+
+        contract.max_per_wallet = None;
+        assert_eq!(contract.mints_left(accounts(1)), Some(U64(1)));
+
+        contract.total_supply = 0;
+        assert_eq!(contract.mints_left(accounts(1)), None);
+        contract.max_per_wallet = Some(3);
+        assert_eq!(contract.mints_left(accounts(1)), Some(U64(3)));
+    }
+
+    /// `setup()` closed at 100 with a redeem deadline of 1000 and `unclaimed` rewards left.
+    fn setup_closed(unclaimed: u128) -> Contract {
+        let mut contract = setup(None, false);
+        contract.unclaimed_balance = unclaimed;
+        testing_env!(context(accounts(0), 0).block_timestamp(100).build());
+        contract.close_collection(U64(1_000), Some(accounts(2)));
+        contract
+    }
+
+    fn as_owner_at(timestamp: u64) {
+        testing_env!(context(accounts(0), 0).block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn close_collection_records_the_closure_and_notifies_the_launchpad() {
+        let contract = setup_closed(0);
+        assert_eq!(contract.closure(), Some((U64(100), U64(1_000), accounts(2))));
+        assert!(get_created_receipts().iter().any(|receipt| receipt.receiver_id == launchpad()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Collection is already closed")]
+    fn close_collection_only_once() {
+        let mut contract = setup_closed(0);
+        contract.close_collection(U64(2_000), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Redeem deadline has to be in the future")]
+    fn close_collection_needs_a_future_deadline() {
+        let mut contract = setup(None, false);
+        as_owner_at(100);
+        contract.close_collection(U64(100), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Collection is closed")]
+    fn closed_collections_stop_minting() {
+        let mut contract = setup_closed(0);
+        contract.sequential_ids = true;
+        testing_env!(context(accounts(1), 10u128.pow(24)).block_timestamp(200).build());
+        contract.nft_mint(None, accounts(1), None, None);
+    }
+
+    #[test]
+    fn sweep_unclaimed_sends_the_rewards_to_the_sweep_account_once() {
+        let mut contract = setup_closed(500);
+        as_owner_at(1_000);
+        contract.sweep_unclaimed();
+        assert!(contract.swept);
+        assert_eq!(contract.unclaimed_balance(), U128(0));
+        assert!(transfers().contains(&(accounts(2), 500)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unclaimed rewards were already swept")]
+    fn sweep_unclaimed_only_once() {
+        let mut contract = setup_closed(500);
+        as_owner_at(1_000);
+        contract.sweep_unclaimed();
+        contract.sweep_unclaimed();
+    }
+
+    #[test]
+    #[should_panic(expected = "Redeem deadline has not passed yet")]
+    fn sweep_unclaimed_waits_for_the_deadline() {
+        let mut contract = setup_closed(500);
+        as_owner_at(999);
+        contract.sweep_unclaimed();
+    }
+
+    #[test]
+    fn collections_are_redeemable_until_the_deadline() {
+        let contract = setup(None, false);
+        as_owner_at(5_000);
+        contract.assert_redeemable();
+
+        let contract = setup_closed(0);
+        as_owner_at(999);
+        contract.assert_redeemable();
+    }
+
+    #[test]
+    #[should_panic(expected = "Redeem deadline of this closed collection has passed")]
+    fn collections_are_not_redeemable_after_the_deadline() {
+        let contract = setup_closed(0);
+        as_owner_at(1_000);
+        contract.assert_redeemable();
+    }
+}
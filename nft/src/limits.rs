@@ -0,0 +1,62 @@
+use crate::*;
+
+impl Contract {
+    /// Check `quantity` more mints by `minter` against the per-transaction and per-wallet
+    /// limits and count them. Every mint path goes through here, whatever the currency.
+    pub(crate) fn internal_use_mint_limits(&mut self, minter: &AccountId, quantity: u64) {
+        require!(quantity > 0, "Nothing to mint");
+        if let Some(max_per_tx) = self.max_per_tx {
+            require!(
+                quantity <= max_per_tx,
+                format!("At most {} tokens can be minted per transaction", max_per_tx)
+            );
+        }
+
+        let minted = self.minted_by_account.get(minter).unwrap_or(0) + quantity;
+        if let Some(max_per_wallet) = self.max_per_wallet {
+            require!(
+                minted <= max_per_wallet,
+                format!("At most {} tokens can be minted per account", max_per_wallet)
+            );
+        }
+        self.minted_by_account.insert(minter, &minted);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Set how many tokens one account can mint in total and in a single transaction.
+    /// `None` removes the limit.
+    pub fn set_mint_limits(&mut self, max_per_wallet: Option<U64>, max_per_tx: Option<U64>) {
+        self.assert_collection_owner();
+        self.max_per_wallet = max_per_wallet.map(|max| max.0);
+        self.max_per_tx = max_per_tx.map(|max| max.0);
+    }
+
+    /// Per-wallet and per-transaction mint limits.
+    pub fn mint_limits(&self) -> (Option<U64>, Option<U64>) {
+        (self.max_per_wallet.map(U64), self.max_per_tx.map(U64))
+    }
+
+    pub fn minted_by(&self, account_id: AccountId) -> U64 {
+        U64(self.minted_by_account.get(&account_id).unwrap_or(0))
+    }
+
+    /// How many more tokens `account_id` can mint, bounded by the per-wallet limit and the
+    /// remaining supply. `None` when neither is limited.
+    pub fn mints_left(&self, account_id: AccountId) -> Option<U64> {
+        let wallet_left = self
+            .max_per_wallet
+            .map(|max| max.saturating_sub(self.minted_by_account.get(&account_id).unwrap_or(0)));
+        let supply_left = if self.total_supply > 0 {
+            Some(u64::try_from(self.total_supply.saturating_sub(self.index)).unwrap_or(u64::MAX))
+        } else {
+            None
+        };
+
+        match (wallet_left, supply_left) {
+            (Some(wallet), Some(supply)) => Some(U64(wallet.min(supply))),
+            (left, None) | (None, left) => left.map(U64),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// A stage of the sale with its own schedule, price and limits, e.g. an allowlist presale
+/// followed by a public sale.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintPhase {
+    pub name: String,
+    //nanosecond timestamps bounding the phase, open ended when `end` is None
+    pub start: U64,
+    pub end: Option<U64>,
+    //price of a token minted during the phase, in the mint currency
+    pub price: U128,
+    //how many tokens one account can mint during the phase
+    pub max_per_wallet: Option<U64>,
+    //whether only accounts on the phase's allowlist can mint
+    pub allowlist_only: bool,
+    //root of a merkle tree of (account, max_quantity) leaves also allowed to mint
+    #[serde(default)]
+    pub merkle_root: Option<Base58CryptoHash>,
+}
+
+/// Proof that (minter, `max_quantity`) is a leaf of the merkle allowlist of the active phase.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AllowlistProof {
+    pub max_quantity: U64,
+    //sibling hashes from the leaf up to the root
+    pub proof: Vec<Base58CryptoHash>,
+}
+
+impl MintPhase {
+    /// Check `proof` against the phase's merkle root. Leaves are `sha256("{account_id}:{max_quantity}")`
+    /// and each pair of nodes is hashed in sorted order.
+    fn verify_allowance(&self, account_id: &AccountId, proof: &AllowlistProof) -> bool {
+        let Some(root) = self.merkle_root else {
+            return false;
+        };
+        let leaf = format!("{}:{}", account_id, proof.max_quantity.0);
+        let computed = proof.proof.iter().fold(env::sha256_array(leaf.as_bytes()), |node, sibling| {
+            let sibling = CryptoHash::from(*sibling);
+            let (left, right) = if node <= sibling { (node, sibling) } else { (sibling, node) };
+            env::sha256_array(&[left, right].concat())
+        });
+        computed == CryptoHash::from(root)
+    }
+}
+
+impl Contract {
+    /// The phase open at the current block, if any.
+    pub(crate) fn internal_current_phase(&self) -> Option<MintPhase> {
+        let now = env::block_timestamp();
+        self.phases
+            .iter()
+            .find(|phase| phase.start.0 <= now && phase.end.map(|end| now < end.0).unwrap_or(true))
+            .cloned()
+    }
+
+    /// Check that `minter` can mint `quantity` tokens now and record them against the active
+    /// phase. Returns the price of one token: the phase price, or `mint_price` when the
+    /// collection has no phases.
+    pub(crate) fn internal_use_phase(
+        &mut self,
+        minter: &AccountId,
+        quantity: u64,
+        allowlist_proof: Option<&AllowlistProof>,
+    ) -> u128 {
+        if self.phases.is_empty() {
+            return self.mint_price;
+        }
+        let phase = self
+            .internal_current_phase()
+            .unwrap_or_else(|| env::panic_str("No mint phase is active"));
+
+        let key = (phase.name.clone(), minter.clone());
+        let mut limit = phase.max_per_wallet.map(|max| max.0);
+        if phase.allowlist_only && !self.allowlist.contains(&key) {
+            let proof = allowlist_proof.unwrap_or_else(|| {
+                env::panic_str(&format!("{} is not on the allowlist of phase {}", minter, phase.name))
+            });
+            require!(phase.verify_allowance(minter, proof), "Invalid allowlist proof");
+            // Merkle allowances are consumed through the phase mint count
+            limit = Some(limit.map_or(proof.max_quantity.0, |max| max.min(proof.max_quantity.0)));
+        }
+
+        let minted = self.phase_mints.get(&key).unwrap_or(0) + quantity;
+        if let Some(limit) = limit {
+            require!(
+                minted <= limit,
+                format!("Mint limit of phase {} reached", phase.name)
+            );
+        }
+        self.phase_mints.insert(&key, &minted);
+
+        phase.price.0
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Add a phase to the sale schedule. Phases can't overlap; `mint_price` and the sale window
+    /// only apply while the collection has no phases.
+    pub fn add_phase(&mut self, phase: MintPhase) {
+        self.assert_collection_owner();
+        require!(!phase.name.is_empty(), "Phase name can't be empty");
+        if let Some(end) = phase.end {
+            require!(phase.start.0 < end.0, "Phase start has to be before its end");
+        }
+        for other in self.phases.iter() {
+            require!(other.name != phase.name, format!("Phase {} already exists", phase.name));
+            let starts_before_other_ends = other.end.map(|end| phase.start.0 < end.0).unwrap_or(true);
+            let ends_after_other_starts = phase.end.map(|end| end.0 > other.start.0).unwrap_or(true);
+            require!(
+                !(starts_before_other_ends && ends_after_other_starts),
+                format!("Phase {} overlaps phase {}", phase.name, other.name)
+            );
+        }
+
+        self.phases.push(phase);
+        self.phases.sort_by_key(|phase| phase.start.0);
+    }
+
+    /// Remove a phase from the schedule. Its allowlist entries are kept, so adding a phase
+    /// with the same name restores them.
+    pub fn remove_phase(&mut self, name: String) {
+        self.assert_collection_owner();
+        let len = self.phases.len();
+        self.phases.retain(|phase| phase.name != name);
+        require!(self.phases.len() < len, format!("Phase {} not found", name));
+    }
+
+    /// Replace the merkle allowlist root of phase `name`; `None` leaves only the stored
+    /// allowlist. Allowance already consumed in the phase still counts after a rotation.
+    pub fn set_merkle_root(&mut self, name: String, merkle_root: Option<Base58CryptoHash>) {
+        self.assert_collection_owner();
+        let phase = self
+            .phases
+            .iter_mut()
+            .find(|phase| phase.name == name)
+            .unwrap_or_else(|| env::panic_str(&format!("Phase {} not found", name)));
+        phase.merkle_root = merkle_root;
+    }
+
+    /// Add accounts to the allowlist of phase `name`. The attached deposit has to cover the
+    /// storage of the new entries; the excess is refunded.
+    #[payable]
+    pub fn add_to_allowlist(&mut self, name: String, account_ids: Vec<AccountId>) {
+        self.assert_collection_owner();
+        let initial_storage = env::storage_usage();
+        for account_id in account_ids {
+            self.allowlist.insert(&(name.clone(), account_id));
+        }
+        refund_storage_deposit(initial_storage);
+    }
+
+    /// Remove accounts from the allowlist of phase `name` and refund the freed storage.
+    pub fn remove_from_allowlist(&mut self, name: String, account_ids: Vec<AccountId>) {
+        self.assert_collection_owner();
+        let initial_storage = env::storage_usage();
+        for account_id in account_ids {
+            self.allowlist.remove(&(name.clone(), account_id));
+        }
+        refund_storage_deposit(initial_storage);
+    }
+
+    /// The sale schedule, ordered by start time.
+    pub fn phases(&self) -> Vec<MintPhase> {
+        self.phases.clone()
+    }
+
+    pub fn current_phase(&self) -> Option<MintPhase> {
+        self.internal_current_phase()
+    }
+
+    pub fn is_allowlisted(&self, name: String, account_id: AccountId) -> bool {
+        self.allowlist.contains(&(name, account_id))
+    }
+
+    /// How many tokens `account_id` minted during phase `name`.
+    pub fn phase_mints_of(&self, name: String, account_id: AccountId) -> U64 {
+        U64(self.phase_mints.get(&(name, account_id)).unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn leaf(account_id: &AccountId, max_quantity: u64) -> CryptoHash {
+        env::sha256_array(format!("{}:{}", account_id, max_quantity).as_bytes())
+    }
+
+    fn node(a: CryptoHash, b: CryptoHash) -> CryptoHash {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        env::sha256_array(&[left, right].concat())
+    }
+
+    fn phase(merkle_root: Option<CryptoHash>) -> MintPhase {
+        MintPhase {
+            name: "presale".to_string(),
+            start: U64(0),
+            end: None,
+            price: U128(1),
+            max_per_wallet: None,
+            allowlist_only: true,
+            merkle_root: merkle_root.map(Base58CryptoHash::from),
+        }
+    }
+
+    fn proof(max_quantity: u64, siblings: &[CryptoHash]) -> AllowlistProof {
+        AllowlistProof {
+            max_quantity: U64(max_quantity),
+            proof: siblings.iter().copied().map(Base58CryptoHash::from).collect(),
+        }
+    }
+
+    #[test]
+    fn verifies_every_leaf_of_the_tree() {
+        testing_env!(VMContextBuilder::new().build());
+        let leaves = [
+            leaf(&accounts(0), 2),
+            leaf(&accounts(1), 1),
+            leaf(&accounts(2), 5),
+            leaf(&accounts(3), 3),
+        ];
+        let left = node(leaves[0], leaves[1]);
+        let right = node(leaves[2], leaves[3]);
+        let phase = phase(Some(node(left, right)));
+
+        assert!(phase.verify_allowance(&accounts(0), &proof(2, &[leaves[1], right])));
+        assert!(phase.verify_allowance(&accounts(1), &proof(1, &[leaves[0], right])));
+        assert!(phase.verify_allowance(&accounts(2), &proof(5, &[leaves[3], left])));
+        assert!(phase.verify_allowance(&accounts(3), &proof(3, &[leaves[2], left])));
+    }
+
+    #[test]
+    fn rejects_another_quantity_or_account() {
+        testing_env!(VMContextBuilder::new().build());
+        let leaves = [leaf(&accounts(0), 2), leaf(&accounts(1), 1)];
+        let phase = phase(Some(node(leaves[0], leaves[1])));
+
+        assert!(phase.verify_allowance(&accounts(0), &proof(2, &[leaves[1]])));
+        assert!(!phase.verify_allowance(&accounts(0), &proof(3, &[leaves[1]])));
+        assert!(!phase.verify_allowance(&accounts(2), &proof(2, &[leaves[1]])));
+        assert!(!phase.verify_allowance(&accounts(0), &proof(2, &[])));
+    }
+
+    #[test]
+    fn rejects_every_proof_without_a_root() {
+        testing_env!(VMContextBuilder::new().build());
+        let leaves = [leaf(&accounts(0), 2), leaf(&accounts(1), 1)];
+        assert!(!phase(None).verify_allowance(&accounts(0), &proof(2, &[leaves[1]])));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::*;
+
+impl Contract {
+    /// Draw a random id from the ids not minted yet. The pool is the positions `0..remaining`
+    /// of a virtual list of ids `1..=total_supply`; a drawn position is filled with the id at the
+    /// end of the pool, so only swapped positions are stored in `id_swaps`.
+    ///
+    /// `drawn` is how many ids were already drawn for the current mint and not counted in
+    /// `index` yet.
+    pub(crate) fn internal_draw_token_id(&mut self, drawn: u128) -> TokenId {
+        let minted = self.index + drawn;
+        require!(self.total_supply > minted, "Exceeded total supply");
+        let remaining = (self.total_supply - minted) as u64;
+
+        // Every draw of a block shares the random seed, so mix in how many ids are gone
+        let seed = env::sha256_array(&[env::random_seed(), minted.to_le_bytes().to_vec()].concat());
+        let mut seed_bytes = [0u8; 8];
+        seed_bytes.copy_from_slice(&seed[..8]);
+        let position = u64::from_le_bytes(seed_bytes) % remaining;
+
+        let last = remaining - 1;
+        let token_id = self.id_swaps.get(&position).unwrap_or(position + 1);
+        if position != last {
+            let last_id = self.id_swaps.get(&last).unwrap_or(last + 1);
+            self.id_swaps.insert(&position, &last_id);
+        }
+        self.id_swaps.remove(&last);
+
+        token_id.to_string()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Assign sequential-mode ids in random order instead of counting up from 1. Needs a fixed
+    /// `total_supply` and can only change before the first mint.
+    pub fn set_random_ids(&mut self, random_ids: bool) {
+        self.assert_collection_owner();
+        require!(self.index == 0, "Id mode can't change after the first mint");
+        if random_ids {
+            require!(self.sequential_ids, "Random ids need contract-assigned ids");
+            require!(self.total_supply > 0, "Random ids need a fixed total supply");
+            require!(
+                self.total_supply <= u128::from(u64::MAX),
+                "Random ids need a total supply that fits in a u64"
+            );
+        }
+        self.random_ids = random_ids;
+    }
+
+    pub fn random_ids(&self) -> bool {
+        self.random_ids
+    }
+
+    /// How many ids are left in the random pool. `None` when the supply is unlimited.
+    pub fn ids_remaining(&self) -> Option<U64> {
+        if self.total_supply == 0 {
+            return None;
+        }
+        Some(U64(u64::try_from(self.total_supply.saturating_sub(self.index)).unwrap_or(u64::MAX)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{context, launchpad, setup};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use std::collections::HashSet;
+
+    #[test]
+    fn draws_every_id_once() {
+        for seed in 0..8u8 {
+            let mut contract = setup(None, false);
+            testing_env!(context(launchpad(), 0).random_seed([seed; 32]).build());
+
+            let mut ids = HashSet::new();
+            // Four ids in one mint, then the rest one mint at a time
+            for drawn in 0..4 {
+                assert!(ids.insert(contract.internal_draw_token_id(drawn)));
+            }
+            contract.index = 4;
+            while contract.index < contract.total_supply {
+                assert!(ids.insert(contract.internal_draw_token_id(0)));
+                contract.index += 1;
+            }
+
+            let expected: HashSet<TokenId> = (1..=10).map(|id: u32| id.to_string()).collect();
+            assert_eq!(ids, expected);
+            assert_eq!(contract.ids_remaining(), Some(U64(0)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeded total supply")]
+    fn stops_once_the_pool_is_empty() {
+        let mut contract = setup(None, false);
+        for drawn in 0..10 {
+            contract.internal_draw_token_id(drawn);
+        }
+        contract.internal_draw_token_id(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Random ids need a total supply that fits in a u64")]
+    fn rejects_a_total_supply_beyond_u64() {
+        let mut contract = setup(None, false);
+        contract.sequential_ids = true;
+        contract.total_supply = u128::from(u64::MAX) + 1;
+        testing_env!(context(accounts(0), 0).build());
+        contract.set_random_ids(true);
+    }
+
+    #[test]
+    fn has_no_pool_without_a_total_supply() {
+        let mut contract = setup(None, false);
+        assert_eq!(contract.ids_remaining(), Some(U64(10)));
+        contract.total_supply = 0;
+        assert_eq!(contract.ids_remaining(), None);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::*;
+
+/// Final metadata location of a collection with a delayed reveal.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Reveal {
+    pub base_uri: String,
+    //token `id` shows the artwork `(id - 1 + starting_index) % supply + 1`
+    pub starting_index: U64,
+    //number of artworks the starting index wraps around
+    pub supply: U64,
+    //block timestamp (in nanoseconds) of the reveal
+    pub revealed_at: U64,
+}
+
+impl Contract {
+    /// Whether the collection committed to a provenance hash and hasn't revealed yet.
+    fn is_hidden(&self) -> bool {
+        self.provenance_hash.is_some() && self.reveal.is_none()
+    }
+
+    /// The token as views show it: placeholder metadata until the reveal, then the metadata
+    /// of the artwork its id maps to.
+    pub(crate) fn internal_displayed_token(&self, mut token: Token) -> Token {
+        if self.is_hidden() {
+            if let Some(metadata) = token.metadata.as_mut() {
+                metadata.description = None;
+                metadata.media = None;
+                metadata.media_hash = None;
+                metadata.extra = None;
+                metadata.reference = self.placeholder_uri.clone();
+                metadata.reference_hash = None;
+            }
+        } else if let (Some(reveal), Ok(id)) = (self.reveal.as_ref(), token.token_id.parse::<u64>()) {
+            if let Some(metadata) = token.metadata.as_mut() {
+                let artwork = (id.saturating_sub(1) + reveal.starting_index.0) % reveal.supply.0 + 1;
+                metadata.reference = Some(format!("{}/{}.json", reveal.base_uri.trim_end_matches('/'), artwork));
+            }
+        }
+        token
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Commit to a provenance hash of the final artwork and hide token metadata behind
+    /// `placeholder_uri` until `reveal`. Only possible before the first mint.
+    pub fn set_provenance(&mut self, provenance_hash: String, placeholder_uri: Option<String>) {
+        self.assert_collection_owner();
+        require!(self.index == 0, "Provenance can't change after the first mint");
+        require!(self.reveal.is_none(), "Collection is already revealed");
+        self.provenance_hash = Some(provenance_hash);
+        self.placeholder_uri = placeholder_uri;
+    }
+
+    /// Reveal the artwork, whenever the owner chooses. The starting index is derived from the
+    /// block's random seed mixed with `offset_seed`, so neither the owner nor minters could pick
+    /// which token gets which artwork. The reveal is final.
+    pub fn reveal(&mut self, base_uri: String, offset_seed: String) -> Reveal {
+        self.assert_collection_owner();
+        let provenance_hash = self
+            .provenance_hash
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Collection has no provenance hash"));
+        require!(self.reveal.is_none(), "Collection is already revealed");
+
+        let supply = if self.total_supply > 0 { self.total_supply } else { self.index };
+        require!(supply > 0, "Nothing to reveal");
+        let supply = u64::try_from(supply).unwrap_or_else(|_| env::panic_str("Supply is too large to reveal"));
+        let seed = env::sha256_array(&[env::random_seed(), offset_seed.into_bytes()].concat());
+        let mut seed_bytes = [0u8; 16];
+        seed_bytes.copy_from_slice(&seed[..16]);
+        let starting_index = (u128::from_le_bytes(seed_bytes) % u128::from(supply)) as u64;
+
+        let reveal = Reveal {
+            base_uri,
+            starting_index: U64(starting_index),
+            supply: U64(supply),
+            revealed_at: U64(env::block_timestamp()),
+        };
+
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.base_uri = Some(reveal.base_uri.clone());
+        self.metadata.set(&metadata);
+        self.reveal = Some(reveal.clone());
+
+        Event::Reveal {
+            base_uri: &reveal.base_uri,
+            starting_index: reveal.starting_index,
+            provenance_hash: &provenance_hash,
+        }
+        .emit();
+
+        reveal
+    }
+
+    /// Provenance hash the collection committed to and its reveal, if any.
+    pub fn provenance(&self) -> (Option<String>, Option<Reveal>) {
+        (self.provenance_hash.clone(), self.reveal.clone())
+    }
+}
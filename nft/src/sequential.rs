@@ -0,0 +1,95 @@
+use crate::*;
+
+//path of a token's metadata JSON under the collection's base_uri
+const TOKEN_URI_TEMPLATE: &str = "{id}.json";
+const DEFAULT_TITLE_PATTERN: &str = "{name} #{id}";
+
+impl Contract {
+    /// Turn what minters asked for into the tokens to mint. With sequential ids the contract
+    /// picks the next ids from `index` (or draws them from the random pool) and builds the
+    /// metadata itself, ignoring the caller's; otherwise every spec needs its own id and metadata.
+    pub(crate) fn internal_resolve_specs(&mut self, specs: Vec<TokenSpec>) -> Vec<(TokenId, AccountId, TokenMetadata)> {
+        specs
+            .into_iter()
+            .enumerate()
+            .map(|(offset, spec)| {
+                if self.sequential_ids {
+                    let token_id = if self.random_ids {
+                        self.internal_draw_token_id(offset as u128)
+                    } else {
+                        (self.index + 1 + offset as u128).to_string()
+                    };
+                    let token_metadata = self.internal_token_metadata(&token_id);
+                    (token_id, spec.token_owner_id, token_metadata)
+                } else {
+                    (
+                        spec.token_id.unwrap_or_else(|| env::panic_str("token_id is required")),
+                        spec.token_owner_id,
+                        spec.token_metadata.unwrap_or_else(|| env::panic_str("token_metadata is required")),
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Metadata of a token with a contract-assigned id: the title follows `title_pattern` and
+    /// the reference points to `{id}.json` under the collection's base_uri.
+    pub(crate) fn internal_token_metadata(&self, token_id: &TokenId) -> TokenMetadata {
+        let metadata = self.metadata.get().unwrap();
+        let title_pattern = self.title_pattern.as_deref().unwrap_or(DEFAULT_TITLE_PATTERN);
+        let path = TOKEN_URI_TEMPLATE.replace("{id}", token_id);
+        let reference = match metadata.base_uri {
+            Some(base_uri) => format!("{}/{}", base_uri.trim_end_matches('/'), path),
+            None => path,
+        };
+
+        TokenMetadata {
+            title: Some(title_pattern.replace("{name}", &metadata.name).replace("{id}", token_id)),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: None,
+            issued_at: Some(env::block_timestamp_ms().to_string()),
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: Some(reference),
+            reference_hash: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Choose between caller-chosen ids and metadata and contract-assigned sequential ids.
+    /// `title_pattern` can use `{name}` (the collection name) and `{id}`, and defaults to
+    /// `{name} #{id}`. Switching the id mode is only possible before the first mint.
+    pub fn set_mint_mode(&mut self, sequential_ids: bool, title_pattern: Option<String>) {
+        self.assert_collection_owner();
+        require!(
+            sequential_ids == self.sequential_ids || self.index == 0,
+            "Id mode can't change after the first mint"
+        );
+        require!(sequential_ids || !self.random_ids, "Turn off random ids first");
+        self.sequential_ids = sequential_ids;
+        self.title_pattern = title_pattern;
+    }
+
+    /// Whether ids are assigned by the contract, and the title pattern of their metadata.
+    pub fn mint_mode(&self) -> (bool, String) {
+        (
+            self.sequential_ids,
+            self.title_pattern.clone().unwrap_or_else(|| DEFAULT_TITLE_PATTERN.to_string()),
+        )
+    }
+
+    /// Id the next sequential mint gets, if ids are assigned by the contract in order.
+    pub fn next_token_id(&self) -> Option<TokenId> {
+        if self.sequential_ids && !self.random_ids {
+            Some((self.index + 1).to_string())
+        } else {
+            None
+        }
+    }
+}
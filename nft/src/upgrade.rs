@@ -0,0 +1,56 @@
+use crate::*;
+
+const GAS_FOR_MIGRATE: Gas = Gas::from_tgas(50);
+
+#[near_bindgen]
+impl Contract {
+    /// Opt in to a code upgrade pushed by the launchpad. Only code whose sha256 matches
+    /// `code_hash` will be accepted; pass `None` to revoke a pending authorization.
+    pub fn authorize_upgrade(&mut self, code_hash: Option<Base58CryptoHash>) {
+        require!(
+            env::predecessor_account_id() == self.tokens.owner_id,
+            "Only the collection owner can authorize upgrades"
+        );
+        self.authorized_upgrade = code_hash;
+    }
+
+    pub fn authorized_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.authorized_upgrade
+    }
+
+    /// Deploy the wasm passed as the raw input of the call and run `migrate` on it.
+    /// Can only be called by the launchpad that deployed this collection, with code the
+    /// owner authorized through `authorize_upgrade`.
+    pub fn update_code(&mut self) -> Promise {
+        require!(
+            env::predecessor_account_id() == self.factory_id,
+            "Only the launchpad can upgrade this collection"
+        );
+        let code = env::input().unwrap_or_else(|| env::panic_str("No code was provided"));
+        let authorized = self
+            .authorized_upgrade
+            .take()
+            .unwrap_or_else(|| env::panic_str("Upgrade was not authorized by the owner"));
+        require!(
+            Base58CryptoHash::from(env::sha256_array(&code)) == authorized,
+            "Code hash does not match the authorized upgrade"
+        );
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                "migrate".to_string(),
+                json!({}).to_string().into_bytes().to_vec(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_MIGRATE,
+            )
+    }
+
+    /// Called on the freshly deployed code by `update_code`. The state is read in the current
+    /// layout; a release that changes the layout has to convert the previous one here.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        env::state_read().unwrap_or_else(|| env::panic_str("Contract state is missing"))
+    }
+}